@@ -1,4 +1,7 @@
-use headless_chrome::{protocol::page::PrintToPdfOptions, Browser};
+use headless_chrome::{
+    protocol::page::{Length, PaperFormat, PrintToPdfOptionsBuilder},
+    Browser,
+};
 use std::{env, fs};
 
 /// Run the example as follows:
@@ -32,11 +35,16 @@ fn main() -> Result<(), failure::Error> {
     println!("PDF successfully created from internet web page.");
 
     // Browse to the file url and render a pdf of the web page.
-    let pdf_options: Option<PrintToPdfOptions> = None; // use chrome's defaults for this example
+    let pdf_options = PrintToPdfOptionsBuilder::default()
+        .paper_format(PaperFormat::A4)
+        .margins(Length::Mm(15.0))
+        .footer("{title} - page {pageNumber} of {totalPages}")
+        .build()
+        .unwrap();
     let local_pdf = tab
         .navigate_to(&file_path)?
         .wait_until_navigated()?
-        .print_to_pdf(pdf_options)?;
+        .print_to_pdf(Some(pdf_options))?;
     fs::write("rust.pdf", &local_pdf)?;
     println!("PDF successfully created from local web page.");
 
@@ -1,5 +1,5 @@
 use crate::browser::tab::Tab;
-use crate::protocol::target::methods::CreateTarget;
+use crate::protocol::target::methods::{CreateTarget, DisposeBrowserContext};
 use failure;
 use std::sync::Arc;
 
@@ -48,4 +48,22 @@ impl<'a> Context<'a> {
         }
         Ok(tabs)
     }
+
+    /// Closes this context, along with all of its tabs. They are removed from the browser's list
+    /// of tabs, and any further calls on them fail with `TabClosed`.
+    pub fn dispose(self) -> Result<(), failure::Error> {
+        let tabs = self.get_tabs()?;
+        self.browser.call_method(DisposeBrowserContext {
+            browser_context_id: &self.id,
+        })?;
+        for tab in tabs {
+            tab.mark_closed();
+        }
+        self.browser
+            .get_tabs()
+            .lock()
+            .unwrap()
+            .retain(|tab| !tab.is_closed());
+        Ok(())
+    }
 }
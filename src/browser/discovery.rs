@@ -0,0 +1,161 @@
+//! Talks to the HTTP side of the DevTools endpoint (`/json/version`, `/json/list`), which Chrome
+//! serves on the same port as the WebSocket when launched with `--remote-debugging-port`.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use failure::{format_err, Error, Fail};
+use log::*;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::protocol::target::TargetId;
+
+#[derive(Debug, Fail)]
+#[fail(display = "DevTools endpoint {} responded with: {}", url, status_line)]
+pub struct DiscoveryFailed {
+    url: String,
+    status_line: String,
+}
+
+/// What `/json/version` tells us about the browser.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BrowserVersion {
+    #[serde(rename = "Browser")]
+    pub browser: String,
+    #[serde(rename = "webSocketDebuggerUrl")]
+    pub web_socket_debugger_url: String,
+}
+
+/// One entry of `/json/list`.
+///
+/// `target_type` is kept as a string because this endpoint reports a few more kinds of target
+/// (e.g. "iframe", "webview") than `protocol::target::TargetType` knows about.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetDescription {
+    pub id: TargetId,
+    #[serde(rename = "type")]
+    pub target_type: String,
+}
+
+impl TargetDescription {
+    pub fn is_page(&self) -> bool {
+        self.target_type == "page"
+    }
+}
+
+pub struct DevToolsEndpoint {
+    address: SocketAddr,
+}
+
+impl DevToolsEndpoint {
+    pub fn new(host: &str, port: u16) -> Result<Self, Error> {
+        let address = (host, port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| format_err!("Couldn't resolve DevTools host: {}", host))?;
+        Ok(Self { address })
+    }
+
+    pub fn version(&self) -> Result<BrowserVersion, Error> {
+        self.get_json("/json/version")
+    }
+
+    pub fn list(&self) -> Result<Vec<TargetDescription>, Error> {
+        self.get_json("/json/list")
+    }
+
+    fn get_json<T>(&self, path: &str) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        trace!("Requesting http://{}{}", self.address, path);
+        let mut stream = TcpStream::connect_timeout(&self.address, Duration::from_secs(10))?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+
+        // Chrome refuses requests whose Host header isn't an IP address or "localhost", so we
+        // always send the resolved address rather than whatever hostname we were given.
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            path, self.address
+        )?;
+
+        let body =
+            read_http_response(BufReader::new(stream)).map_err(|status_line| DiscoveryFailed {
+                url: format!("http://{}{}", self.address, path),
+                status_line,
+            })?;
+
+        Ok(serde_json::from_str(&body)?)
+    }
+}
+
+/// Returns the body of a `200 OK` response, or the status line of anything else.
+fn read_http_response<R: BufRead>(mut reader: R) -> Result<String, String> {
+    let mut status_line = String::new();
+    reader
+        .read_line(&mut status_line)
+        .map_err(|e| e.to_string())?;
+    let status_line = status_line.trim_end().to_string();
+
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        return Err(status_line);
+    }
+
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).map_err(|e| e.to_string())? == 0 {
+            break;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let mut parts = header.splitn(2, ':');
+        if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    // Chrome sets a Content-Length and may keep the socket open afterwards, so only read until
+    // the end of the stream when we haven't been told how much to expect.
+    let mut body = Vec::new();
+    if let Some(length) = content_length {
+        body.resize(length, 0);
+        reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    } else {
+        reader.read_to_end(&mut body).map_err(|e| e.to_string())?;
+    }
+
+    String::from_utf8(body).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_body_of_devtools_response() {
+        let raw = "HTTP/1.1 200 OK\r\n\
+                   Content-Length:12\r\n\
+                   Content-Type:application/json; charset=UTF-8\r\n\
+                   \r\n\
+                   {\"id\":\"ABC\"}next response";
+        assert_eq!(
+            Ok("{\"id\":\"ABC\"}".to_string()),
+            read_http_response(raw.as_bytes())
+        );
+
+        let raw = "HTTP/1.1 500 Internal Server Error\r\n\r\n";
+        assert_eq!(
+            Err("HTTP/1.1 500 Internal Server Error".to_string()),
+            read_http_response(raw.as_bytes())
+        );
+    }
+}
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
 
-use failure::Error;
+use failure::{Error, Fail};
 use log::*;
 use serde;
 use which::which;
@@ -11,21 +11,27 @@ use which::which;
 pub use process::LaunchOptionsBuilder;
 use process::{LaunchOptions, Process};
 pub use tab::Tab;
+pub use transport::BrowserCrashed;
 use transport::Transport;
 
 use crate::browser::context::Context;
+use crate::browser::tab_pool::TabPool;
 use crate::protocol::browser::methods::GetVersion;
 pub use crate::protocol::browser::methods::VersionInformationReturnObject;
+use crate::protocol::page::PrintToPdfOptions;
+use crate::protocol::runtime::methods::Evaluate;
 use crate::protocol::target::methods::{CreateTarget, SetDiscoverTargets};
 use crate::protocol::{self, Event};
 use crate::util;
 use std::sync::mpsc::{RecvTimeoutError, TryRecvError};
 
 pub mod context;
+mod discovery;
 #[cfg(feature = "fetch")]
 mod fetcher;
 mod process;
 pub mod tab;
+pub mod tab_pool;
 mod transport;
 
 /// A handle to an instance of Chrome / Chromium, which wraps a WebSocket connection to its debugging port.
@@ -66,8 +72,24 @@ pub struct Browser {
     transport: Arc<Transport>,
     tabs: Arc<Mutex<Vec<Arc<Tab>>>>,
     loop_shutdown_tx: mpsc::Sender<()>,
+    event_listeners: EventListeners,
 }
 
+/// Things that happen to the browser as a whole, see `Browser::listen_to_events`.
+#[derive(Debug, Clone)]
+pub enum BrowserEvent {
+    /// The connection to Chrome was lost without the `Browser` having been dropped, e.g.
+    /// because the process crashed. Any method calls that were waiting for a response have
+    /// failed with the enclosed error.
+    Disconnected(BrowserCrashed),
+}
+
+type EventListeners = Arc<Mutex<Vec<mpsc::Sender<BrowserEvent>>>>;
+
+#[derive(Debug, Fail)]
+#[fail(display = "Only browsers started with Browser::new can be relaunched")]
+pub struct CannotRelaunch {}
+
 impl Browser {
     /// Launch a new Chrome browser.
     ///
@@ -75,24 +97,125 @@ impl Browser {
     /// The browser process will be killed when this struct is dropped.
     pub fn new(launch_options: LaunchOptions) -> Result<Self, Error> {
         let process = Process::new(launch_options)?;
-        let process_id = process.get_id();
 
         let transport = Arc::new(Transport::new(
             process.debug_ws_url.clone(),
-            Some(process_id),
+            Some(process.handle()),
         )?);
 
-        Self::create_browser(Some(process), transport)
+        Self::create_browser(Some(process), transport, Arc::new(Mutex::new(vec![])))
     }
 
     pub fn connect(debug_ws_url: String) -> Result<Self, Error> {
         let transport = Arc::new(Transport::new(debug_ws_url, None)?);
         trace!("created transport");
 
-        Self::create_browser(None, transport)
+        Self::create_browser(None, transport, Arc::new(Mutex::new(vec![])))
+    }
+
+    /// Connect to an already running Chrome via its DevTools HTTP endpoint, e.g. one started with
+    /// `--remote-debugging-port=9222` by some other tooling.
+    ///
+    /// The browser's WebSocket URL is looked up via `/json/version`, and we wait until every page
+    /// listed by `/json/list` is available through `get_tabs`, so that tabs which were open before
+    /// we connected can be reused.
+    ///
+    /// ```rust,no_run
+    /// # use failure::Error;
+    /// # fn main() -> Result<(), Error> {
+    /// #
+    /// use headless_chrome::Browser;
+    /// let browser = Browser::connect_to_port("127.0.0.1", 9222)?;
+    /// for tab in browser.get_tabs().lock().unwrap().iter() {
+    ///     println!("Already open: {}", tab.get_url());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn connect_to_port(host: &str, port: u16) -> Result<Self, Error> {
+        let endpoint = discovery::DevToolsEndpoint::new(host, port)?;
+
+        let version = endpoint.version()?;
+        debug!(
+            "Discovered {} at {}",
+            version.browser, version.web_socket_debugger_url
+        );
+
+        let existing_page_ids: Vec<_> = endpoint
+            .list()?
+            .into_iter()
+            .filter(discovery::TargetDescription::is_page)
+            .map(|target| target.id)
+            .collect();
+
+        let browser = Self::connect(version.web_socket_debugger_url)?;
+
+        util::Wait::with_timeout(Duration::from_secs(10)).until(|| {
+            let tabs = browser.tabs.lock().unwrap();
+            if existing_page_ids
+                .iter()
+                .all(|id| tabs.iter().any(|tab| tab.get_target_id() == id))
+            {
+                Some(())
+            } else {
+                None
+            }
+        })?;
+
+        Ok(browser)
+    }
+
+    /// Kills the Chrome process (if it's still running) and starts a new one with the same
+    /// `LaunchOptions` that were passed to `Browser::new`.
+    ///
+    /// This is meant for recovering after a `BrowserEvent::Disconnected`. All existing tabs are
+    /// gone afterwards, but anything listening via `listen_to_events` keeps receiving events.
+    ///
+    /// ```rust,no_run
+    /// # use failure::Error;
+    /// # fn main() -> Result<(), Error> {
+    /// #
+    /// use headless_chrome::{Browser, LaunchOptionsBuilder, browser::BrowserEvent};
+    /// let mut browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+    /// let events = browser.listen_to_events();
+    /// // ... later, e.g. after a method call failed with BrowserCrashed:
+    /// if let Ok(BrowserEvent::Disconnected(crash)) = events.try_recv() {
+    ///     println!("Chrome went away ({}), starting it again", crash);
+    ///     browser.relaunch()?;
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn relaunch(&mut self) -> Result<(), Error> {
+        let process = self.process.take().ok_or(CannotRelaunch {})?;
+        self.transport.shutdown();
+        let _ = self.loop_shutdown_tx.send(());
+
+        let process = process.relaunch()?;
+        let transport = Arc::new(Transport::new(
+            process.debug_ws_url.clone(),
+            Some(process.handle()),
+        )?);
+
+        *self = Self::create_browser(Some(process), transport, Arc::clone(&self.event_listeners))?;
+        Ok(())
+    }
+
+    /// Returns a channel which receives a `BrowserEvent` whenever something happens to the
+    /// browser as a whole, such as it crashing.
+    pub fn listen_to_events(&self) -> mpsc::Receiver<BrowserEvent> {
+        let (events_tx, events_rx) = mpsc::channel();
+        self.event_listeners.lock().unwrap().push(events_tx);
+        events_rx
     }
 
-    fn create_browser(process: Option<Process>, transport: Arc<Transport>) -> Result<Self, Error> {
+    fn create_browser(
+        process: Option<Process>,
+        transport: Arc<Transport>,
+        event_listeners: EventListeners,
+    ) -> Result<Self, Error> {
         let tabs = Arc::new(Mutex::new(vec![]));
 
         let (shutdown_tx, shutdown_rx) = mpsc::channel();
@@ -102,6 +225,7 @@ impl Browser {
             tabs,
             transport,
             loop_shutdown_tx: shutdown_tx,
+            event_listeners,
         };
 
         let incoming_events_rx = browser.transport.listen_to_browser_events();
@@ -218,6 +342,12 @@ impl Browser {
         Ok(Context::new(self, context_id))
     }
 
+    /// Opens `size` tabs to be borrowed with `TabPool::get`, optionally each in a context of its
+    /// own. See [TabPool](tab_pool/struct.TabPool.html).
+    pub fn new_tab_pool(&self, size: usize, separate_contexts: bool) -> Result<TabPool, Error> {
+        TabPool::new(self, size, separate_contexts)
+    }
+
     /// Get version information
     ///
     /// ```rust
@@ -236,6 +366,55 @@ impl Browser {
         self.call_method(GetVersion {})
     }
 
+    /// Renders `html` as a PDF in a new tab, which is closed again afterwards.
+    ///
+    /// Relative URLs of images, stylesheets and fonts in `html` are resolved against
+    /// `base_url`, if given (by adding a `<base>` element to its head). The PDF is only printed
+    /// once everything has loaded, web fonts included.
+    ///
+    /// ```rust,no_run
+    /// # use failure::Error;
+    /// # fn main() -> Result<(), Error> {
+    /// #
+    /// # use headless_chrome::{Browser, LaunchOptionsBuilder};
+    /// # let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+    /// let invoice = "<h1>Invoice</h1><img src=\"logo.png\">";
+    /// let pdf = browser.html_to_pdf(invoice, Some("https://example.com/assets/"), None)?;
+    /// std::fs::write("invoice.pdf", &pdf)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn html_to_pdf(
+        &self,
+        html: &str,
+        base_url: Option<&str>,
+        options: Option<PrintToPdfOptions>,
+    ) -> Result<Vec<u8>, Error> {
+        let html = match base_url {
+            Some(base_url) => with_base_url(html, base_url),
+            None => html.to_string(),
+        };
+
+        let tab = self.new_tab()?;
+        let pdf = tab
+            .set_content(&html)
+            .and_then(|tab| {
+                tab.call_method(Evaluate {
+                    expression: "document.fonts.ready.then(() => true)",
+                    return_by_value: true,
+                    await_promise: true,
+                })
+            })
+            .and_then(|_| tab.print_to_pdf(options));
+
+        // close the tab even if printing failed, but report the original error
+        let closed = tab.close(false);
+        let pdf = pdf?;
+        closed?;
+        Ok(pdf)
+    }
+
     fn handle_browser_level_events(
         &self,
         events_rx: mpsc::Receiver<Event>,
@@ -244,6 +423,7 @@ impl Browser {
     ) {
         let tabs = Arc::clone(&self.tabs);
         let transport = Arc::clone(&self.transport);
+        let event_listeners = Arc::clone(&self.event_listeners);
 
         std::thread::spawn(move || {
             trace!("Starting browser's event handling loop");
@@ -257,20 +437,23 @@ impl Browser {
                 }
 
                 match events_rx.recv_timeout(Duration::from_millis(20_000)) {
-                    Err(recv_timeout_error) => {
-                        match recv_timeout_error {
-                            RecvTimeoutError::Timeout => {
-                                error!(
-                                    "Got a timeout while listening for browser events (Chrome #{:?})",
-                                    process_id
-                                );
-                            }
-                            RecvTimeoutError::Disconnected => {
-                                debug!(
-                                    "Browser event sender disconnected while loop was waiting (Chrome #{:?})",
-                                    process_id
-                                );
-                            }
+                    Err(RecvTimeoutError::Timeout) => {
+                        trace!(
+                            "No browser events for a while, still listening (Chrome #{:?})",
+                            process_id
+                        );
+                    }
+                    Err(RecvTimeoutError::Disconnected) => {
+                        debug!(
+                            "Browser event sender disconnected while loop was waiting (Chrome #{:?})",
+                            process_id
+                        );
+                        if let Some(crash) = transport.get_crash() {
+                            event_listeners.lock().unwrap().retain(|listener| {
+                                listener
+                                    .send(BrowserEvent::Disconnected(crash.clone()))
+                                    .is_ok()
+                            });
                         }
                         break;
                     }
@@ -296,15 +479,40 @@ impl Browser {
                                 trace!("Target info changed: {:?}", target_info);
                                 if target_info.target_type.is_page() {
                                     let locked_tabs = tabs.lock().unwrap();
-                                    let updated_tab = locked_tabs
+                                    // closed tabs are no longer in the list
+                                    if let Some(updated_tab) = locked_tabs
                                         .iter()
                                         .find(|tab| *tab.get_target_id() == target_info.target_id)
-                                        .expect("got TargetInfoChanged event about a tab not in our list");
-                                    updated_tab.update_target_info(target_info);
+                                    {
+                                        updated_tab.update_target_info(target_info);
+                                    }
+                                }
+                            }
+                            Event::TargetCrashed(ev) => {
+                                let target_id = ev.params.target_id;
+                                trace!("Target crashed: {:?}", target_id);
+                                // the target sticks around until it's closed, at which point
+                                // TargetDestroyed takes it out of the list
+                                let locked_tabs = tabs.lock().unwrap();
+                                if let Some(crashed_tab) = locked_tabs
+                                    .iter()
+                                    .find(|tab| *tab.get_target_id() == target_id)
+                                {
+                                    crashed_tab.mark_crashed();
                                 }
                             }
                             Event::TargetDestroyed(ev) => {
-                                trace!("Target destroyed: {:?}", ev.params.target_id);
+                                let target_id = ev.params.target_id;
+                                trace!("Target destroyed: {:?}", target_id);
+                                let mut locked_tabs = tabs.lock().unwrap();
+                                locked_tabs.retain(|tab| {
+                                    if *tab.get_target_id() == target_id {
+                                        tab.mark_closed();
+                                        false
+                                    } else {
+                                        true
+                                    }
+                                });
                             }
                             _ => {
                                 let mut raw_event = format!("{:?}", event);
@@ -340,11 +548,37 @@ impl Browser {
 impl Drop for Browser {
     fn drop(&mut self) {
         info!("Dropping browser");
-        let _ = self.loop_shutdown_tx.send(());
+        // shut the transport down first, so that it knows the disconnect is deliberate
         self.transport.shutdown();
+        let _ = self.loop_shutdown_tx.send(());
     }
 }
 
+/// Adds a `<base href="base_url">` element to the head of `html`.
+fn with_base_url(html: &str, base_url: &str) -> String {
+    let base = format!("<base href=\"{}\">", base_url.replace('"', "&quot;"));
+    // lowercasing ASCII leaves byte offsets alone
+    let lowercase = html.to_ascii_lowercase();
+    let after_tag = |name: &str| {
+        let mut from = 0;
+        while let Some(start) = lowercase[from..].find(name).map(|i| i + from) {
+            let rest = &lowercase[start + name.len()..];
+            if rest.starts_with('>') || rest.starts_with(char::is_whitespace) {
+                return rest.find('>').map(|end| start + name.len() + end + 1);
+            }
+            from = start + name.len();
+        }
+        None
+    };
+    let position = after_tag("<head")
+        .or_else(|| after_tag("<!doctype"))
+        .unwrap_or(0);
+
+    let mut html = html.to_string();
+    html.insert_str(position, &base);
+    html
+}
+
 /// Returns the path to Chrome's executable.
 ///
 /// If the `CHROME` environment variable is set, `default_executable` will
@@ -395,3 +629,29 @@ pub fn default_executable() -> Result<std::path::PathBuf, String> {
 
     Err("Could not auto detect a chrome executable".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_base_url_to_head() {
+        let base = "<base href=\"https://example.com/\">";
+        assert_eq!(
+            format!("<html><head lang=\"en\">{}<title>", base),
+            with_base_url("<html><head lang=\"en\"><title>", "https://example.com/")
+        );
+        assert_eq!(
+            format!("<!DOCTYPE html>{}<header>", base),
+            with_base_url("<!DOCTYPE html><header>", "https://example.com/")
+        );
+        assert_eq!(
+            format!("{}<p>Hi", base),
+            with_base_url("<p>Hi", "https://example.com/")
+        );
+        assert_eq!(
+            "<base href=\"https://example.com/?q=&quot;\">",
+            with_base_url("", "https://example.com/?q=\"")
+        );
+    }
+}
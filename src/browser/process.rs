@@ -5,11 +5,12 @@ use rand::thread_rng;
 use regex::Regex;
 
 use std::{
-    borrow::BorrowMut,
     ffi::OsStr,
     io::{prelude::*, BufRead, BufReader},
     net,
-    process::{Child, Command, Stdio},
+    path::PathBuf,
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{Arc, Mutex},
     time::Duration,
 };
 
@@ -26,6 +27,7 @@ use crate::util;
 pub struct Process {
     child_process: TemporaryProcess,
     pub debug_ws_url: String,
+    launch_spec: LaunchSpec,
 }
 
 #[derive(Debug, Fail)]
@@ -47,12 +49,79 @@ pub(crate) fn get_chrome_path_from_registry() -> Option<std::path::PathBuf> {
         .ok()
 }
 
-struct TemporaryProcess(Child);
+/// A shared handle to the Chrome child process, so that other threads (e.g. the transport's)
+/// can find out whether and how it exited.
+#[derive(Clone)]
+pub struct ProcessHandle(Arc<Mutex<Child>>);
+
+impl ProcessHandle {
+    pub fn id(&self) -> u32 {
+        self.0.lock().unwrap().id()
+    }
+
+    /// Returns the exit status if the process has exited, without blocking.
+    pub fn try_exit_status(&self) -> Option<ExitStatus> {
+        self.0
+            .lock()
+            .unwrap()
+            .try_wait()
+            .ok()
+            .and_then(|status| status)
+    }
+}
+
+struct TemporaryProcess(ProcessHandle);
 
 impl Drop for TemporaryProcess {
     fn drop(&mut self) {
-        info!("Killing Chrome. PID: {}", self.0.id());
-        self.0.kill().and_then(|_| self.0.wait()).ok();
+        let mut child = (self.0).0.lock().unwrap();
+        info!("Killing Chrome. PID: {}", child.id());
+        child.kill().and_then(|_| child.wait()).ok();
+    }
+}
+
+/// The parts of `LaunchOptions` needed to start Chrome, resolved and owned so that the same
+/// browser can be started again after it has crashed.
+#[derive(Clone)]
+struct LaunchSpec {
+    path: PathBuf,
+    port: Option<u16>,
+    args: Vec<String>,
+}
+
+impl LaunchSpec {
+    fn new(launch_options: &LaunchOptions) -> Result<Self, Error> {
+        let path = launch_options
+            .path
+            .clone()
+            .ok_or_else(|| format_err!("Chrome path required"))?;
+
+        let mut args = vec![];
+
+        if let Some((width, height)) = launch_options.window_size {
+            args.push(format!("--window-size={},{}", width, height));
+        }
+
+        if launch_options.headless {
+            args.push("--headless".to_string());
+        }
+
+        if !launch_options.sandbox {
+            args.push("--no-sandbox".to_string());
+        }
+
+        args.extend(
+            launch_options
+                .extensions
+                .iter()
+                .map(|e| format!("--load-extension={}", e.to_str().unwrap())),
+        );
+
+        Ok(Self {
+            path,
+            port: launch_options.port,
+            args,
+        })
     }
 }
 
@@ -118,7 +187,18 @@ impl Process {
             }
         }
 
-        let mut process = Self::start_process(&launch_options)?;
+        Self::launch(LaunchSpec::new(&launch_options)?)
+    }
+
+    /// Kills this process (if it's still running) and starts a new one with the same options.
+    pub fn relaunch(self) -> Result<Self, Error> {
+        let launch_spec = self.launch_spec.clone();
+        drop(self);
+        Self::launch(launch_spec)
+    }
+
+    fn launch(launch_spec: LaunchSpec) -> Result<Self, Error> {
+        let mut process = Self::start_process(&launch_spec)?;
 
         info!("Started Chrome. PID: {}", process.0.id());
 
@@ -129,7 +209,8 @@ impl Process {
                 return Err(ChromeLaunchError::NoAvailablePorts {}.into());
             }
 
-            match Self::ws_url_from_output(process.0.borrow_mut()) {
+            let ws_url_result = Self::ws_url_from_output(&mut (process.0).0.lock().unwrap());
+            match ws_url_result {
                 Ok(debug_ws_url) => {
                     url = debug_ws_url;
                     debug!("Found debugging WS URL: {:?}", url);
@@ -137,8 +218,8 @@ impl Process {
                 }
                 Err(error) => {
                     trace!("Problem getting WebSocket URL from Chrome: {}", error);
-                    if launch_options.port.is_none() {
-                        process = Self::start_process(&launch_options)?;
+                    if launch_spec.port.is_none() {
+                        process = Self::start_process(&launch_spec)?;
                     } else {
                         return Err(error);
                     }
@@ -155,23 +236,18 @@ impl Process {
         Ok(Self {
             child_process: process,
             debug_ws_url: url,
+            launch_spec,
         })
     }
 
-    fn start_process(launch_options: &LaunchOptions) -> Result<TemporaryProcess, Error> {
-        let debug_port = if let Some(port) = launch_options.port {
+    fn start_process(launch_spec: &LaunchSpec) -> Result<TemporaryProcess, Error> {
+        let debug_port = if let Some(port) = launch_spec.port {
             port
         } else {
             get_available_port().ok_or(ChromeLaunchError::NoAvailablePorts {})?
         };
         let port_option = format!("--remote-debugging-port={}", debug_port);
 
-        let window_size_option = if let Some((width, height)) = launch_options.window_size {
-            format!("--window-size={},{}", width, height)
-        } else {
-            String::from("")
-        };
-
         // NOTE: picking random data dir so that each a new browser instance is launched
         // (see man google-chrome)
         let user_data_dir = ::tempfile::Builder::new()
@@ -188,39 +264,16 @@ impl Process {
             data_dir_option.as_str(),
         ];
 
-        if !window_size_option.is_empty() {
-            args.extend(&[window_size_option.as_str()]);
-        }
-
-        if launch_options.headless {
-            args.extend(&["--headless"]);
-        }
-
-        if !launch_options.sandbox {
-            args.extend(&["--no-sandbox"]);
-        }
+        args.extend(launch_spec.args.iter().map(String::as_str));
 
-        let extension_args: Vec<String> = launch_options
-            .extensions
-            .iter()
-            .map(|e| format!("--load-extension={}", e.to_str().unwrap()))
-            .collect();
+        info!("Launching Chrome binary at {:?}", &launch_spec.path);
 
-        args.extend(extension_args.iter().map(String::as_str));
-
-        let path = launch_options
-            .path
-            .as_ref()
-            .ok_or_else(|| format_err!("Chrome path required"))?;
-
-        info!("Launching Chrome binary at {:?}", &path);
-
-        let process = TemporaryProcess(
-            Command::new(&path)
+        let process = TemporaryProcess(ProcessHandle(Arc::new(Mutex::new(
+            Command::new(&launch_spec.path)
                 .args(&args)
                 .stderr(Stdio::piped())
                 .spawn()?,
-        );
+        ))));
         Ok(process)
     }
 
@@ -279,6 +332,10 @@ impl Process {
     pub fn get_id(&self) -> u32 {
         self.child_process.0.id()
     }
+
+    pub fn handle(&self) -> ProcessHandle {
+        self.child_process.0.clone()
+    }
 }
 
 fn get_available_port() -> Option<u16> {
@@ -0,0 +1,686 @@
+//! Turns the JS coverage collected by `Tab::take_precise_js_coverage` into reports in the lcov
+//! and Istanbul formats, which CI services and coverage viewers understand.
+//!
+//! Chrome reports coverage as ranges of offsets into each script's source: one range per
+//! function, giving the number of times it was called, followed by ranges for the blocks within
+//! it (the branches of an `if`, say) whose count differs from the function's. Ranges are nested,
+//! and the innermost one containing a piece of code has the right count for it.
+//!
+//! Lines count as executable if they contain anything but whitespace, and get the count of the
+//! innermost range that contains all of them.
+//!
+//! CSS coverage, as returned by `Tab::stop_css_coverage`, is simpler: each stylesheet's rules
+//! were either used or not.
+//!
+//! ```rust,no_run
+//! # use failure::Error;
+//! # fn main() -> Result<(), Error> {
+//! #
+//! use headless_chrome::browser::tab::coverage;
+//! # use headless_chrome::{Browser, LaunchOptionsBuilder};
+//! # let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+//! let tab = browser.wait_for_initial_tab()?;
+//! tab.enable_profiler()?.start_js_coverage()?;
+//! tab.navigate_to("https://www.wikipedia.org")?.wait_until_navigated()?;
+//!
+//! let scripts = tab.take_js_coverage_report(Some("https://www.wikipedia.org/*"))?;
+//! std::fs::write("lcov.info", coverage::to_lcov(&scripts))?;
+//! std::fs::write("coverage.json", coverage::to_istanbul(&scripts).to_string())?;
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt::Write;
+use std::ops::Range;
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::protocol::css::RuleUsage;
+use crate::protocol::profiler::{CoverageRange, ScriptCoverage};
+
+/// A position in a script's source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Position {
+    /// One-based
+    pub line: u32,
+    /// Zero-based, in UTF-16 code units (like the indexes of JS strings)
+    pub column: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineReport {
+    /// One-based
+    pub line: u32,
+    /// Where the line's code (ignoring whitespace) starts and ends
+    pub start: Position,
+    pub end: Position,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionReport {
+    /// Anonymous functions are called "(anonymous_N)", N being their index in the script.
+    pub name: String,
+    pub start: Position,
+    pub end: Position,
+    /// How many times the function was called
+    pub count: u32,
+}
+
+/// A block within a function that was executed a different number of times than the function
+/// itself, such as one side of an `if` or the code after an early `return`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchReport {
+    pub start: Position,
+    pub end: Position,
+    pub count: u32,
+}
+
+/// The coverage of one script, by line, function and branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptReport {
+    pub url: String,
+    /// Executable lines only, in order
+    pub lines: Vec<LineReport>,
+    /// Functions in the script, not including its top level
+    pub functions: Vec<FunctionReport>,
+    pub branches: Vec<BranchReport>,
+}
+
+impl ScriptReport {
+    /// Maps the offsets of `coverage` to positions in `source`, the script's source.
+    pub fn new(coverage: &ScriptCoverage, source: &str) -> Self {
+        let lines = Lines::new(source);
+
+        let mut ranges: Vec<&CoverageRange> = coverage
+            .functions
+            .iter()
+            .flat_map(|function| function.ranges.iter())
+            .collect();
+        // outer ranges come before the ranges nested in them, which then override their counts
+        ranges.sort_by_key(|range| (range.start_offset, std::cmp::Reverse(range.end_offset)));
+        let mut counts = vec![0; lines.lines.len()];
+        for range in ranges {
+            let first = lines.index_of(range.start_offset);
+            for (index, line) in lines.lines.iter().enumerate().skip(first) {
+                if line.start >= range.end_offset {
+                    break;
+                }
+                if line.content_start >= range.start_offset && line.content_end <= range.end_offset
+                {
+                    counts[index] = range.count;
+                }
+            }
+        }
+
+        let line_reports = lines
+            .lines
+            .iter()
+            .zip(counts)
+            .enumerate()
+            .filter(|(_, (line, _))| line.content_start < line.content_end)
+            .map(|(index, (line, count))| LineReport {
+                line: saturating_u32(index + 1),
+                start: lines.position_of(line.content_start),
+                end: lines.position_of(line.content_end),
+                count,
+            })
+            .collect();
+
+        let mut functions = vec![];
+        let mut branches = vec![];
+        for (index, function) in coverage.functions.iter().enumerate() {
+            let (function_range, block_ranges) = match function.ranges.split_first() {
+                Some(ranges) => ranges,
+                None => continue,
+            };
+            // the script itself shows up as a nameless function spanning all of it
+            let is_top_level =
+                function.function_name.is_empty() && function_range.start_offset == 0;
+            if !is_top_level {
+                let name = if function.function_name.is_empty() {
+                    format!("(anonymous_{})", index)
+                } else {
+                    function.function_name.clone()
+                };
+                functions.push(FunctionReport {
+                    name,
+                    start: lines.position_of(function_range.start_offset),
+                    end: lines.position_of(function_range.end_offset),
+                    count: function_range.count,
+                });
+            }
+            branches.extend(block_ranges.iter().map(|range| BranchReport {
+                start: lines.position_of(range.start_offset),
+                end: lines.position_of(range.end_offset),
+                count: range.count,
+            }));
+        }
+
+        Self {
+            url: coverage.url.clone(),
+            lines: line_reports,
+            functions,
+            branches,
+        }
+    }
+}
+
+/// Whether `url` matches `pattern`, in which '*' stands for any number of characters and '?'
+/// for exactly one (the same as in the URL patterns of `Network.setRequestInterception`).
+pub fn url_matches(pattern: &str, url: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let url: Vec<char> = url.chars().collect();
+
+    // where to resume from if what follows the last '*' fails to match
+    let mut backtrack = None;
+    let (mut p, mut u) = (0, 0);
+    while u < url.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, u));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == url[u] => {
+                p += 1;
+                u += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    u = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// For line numbers and lengths, which never get anywhere near `u32::MAX` in practice.
+fn saturating_u32(n: usize) -> u32 {
+    u32::try_from(n).unwrap_or(u32::MAX)
+}
+
+/// Function names end at the line's end and are preceded by a comma-separated number, so
+/// commas and line breaks in computed names would corrupt the tracefile.
+fn lcov_function_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c == ',' || c.is_control() { '_' } else { c })
+        .collect()
+}
+
+/// The coverage of `scripts` as an lcov tracefile (`lcov.info`).
+pub fn to_lcov(scripts: &[ScriptReport]) -> String {
+    let mut lcov = String::new();
+    for script in scripts {
+        // writing to a String can't fail
+        let _ = write_lcov_record(&mut lcov, script);
+    }
+    lcov
+}
+
+fn write_lcov_record(lcov: &mut String, script: &ScriptReport) -> std::fmt::Result {
+    writeln!(lcov, "TN:\nSF:{}", script.url)?;
+    for function in &script.functions {
+        writeln!(
+            lcov,
+            "FN:{},{}",
+            function.start.line,
+            lcov_function_name(&function.name)
+        )?;
+    }
+    for function in &script.functions {
+        writeln!(
+            lcov,
+            "FNDA:{},{}",
+            function.count,
+            lcov_function_name(&function.name)
+        )?;
+    }
+    writeln!(lcov, "FNF:{}", script.functions.len())?;
+    writeln!(
+        lcov,
+        "FNH:{}",
+        script.functions.iter().filter(|f| f.count > 0).count()
+    )?;
+    for (index, branch) in script.branches.iter().enumerate() {
+        writeln!(
+            lcov,
+            "BRDA:{},{},0,{}",
+            branch.start.line, index, branch.count
+        )?;
+    }
+    writeln!(lcov, "BRF:{}", script.branches.len())?;
+    writeln!(
+        lcov,
+        "BRH:{}",
+        script.branches.iter().filter(|b| b.count > 0).count()
+    )?;
+    for line in &script.lines {
+        writeln!(lcov, "DA:{},{}", line.line, line.count)?;
+    }
+    writeln!(lcov, "LF:{}", script.lines.len())?;
+    writeln!(
+        lcov,
+        "LH:{}",
+        script.lines.iter().filter(|l| l.count > 0).count()
+    )?;
+    writeln!(lcov, "end_of_record")
+}
+
+/// The coverage of `scripts` in the JSON format of Istanbul (`coverage-final.json`), as used
+/// by nyc and Jest. Each line is reported as a statement.
+pub fn to_istanbul(scripts: &[ScriptReport]) -> Value {
+    let location = |start: Position, end: Position| json!({ "start": start, "end": end });
+
+    let mut files = Map::new();
+    for script in scripts {
+        let mut statement_map = Map::new();
+        let mut statements = Map::new();
+        for (index, line) in script.lines.iter().enumerate() {
+            statement_map.insert(index.to_string(), location(line.start, line.end));
+            statements.insert(index.to_string(), json!(line.count));
+        }
+
+        let mut fn_map = Map::new();
+        let mut functions = Map::new();
+        for (index, function) in script.functions.iter().enumerate() {
+            let loc = location(function.start, function.end);
+            fn_map.insert(
+                index.to_string(),
+                json!({
+                    "name": function.name,
+                    "decl": loc,
+                    "loc": loc,
+                    "line": function.start.line,
+                }),
+            );
+            functions.insert(index.to_string(), json!(function.count));
+        }
+
+        let mut branch_map = Map::new();
+        let mut branches = Map::new();
+        for (index, branch) in script.branches.iter().enumerate() {
+            let loc = location(branch.start, branch.end);
+            branch_map.insert(
+                index.to_string(),
+                json!({
+                    "loc": loc,
+                    "type": "branch",
+                    "locations": [loc],
+                    "line": branch.start.line,
+                }),
+            );
+            branches.insert(index.to_string(), json!([branch.count]));
+        }
+
+        files.insert(
+            script.url.clone(),
+            json!({
+                "path": script.url,
+                "statementMap": statement_map,
+                "fnMap": fn_map,
+                "branchMap": branch_map,
+                "s": statements,
+                "f": functions,
+                "b": branches,
+            }),
+        );
+    }
+    Value::Object(files)
+}
+
+/// Which parts of a stylesheet's text were used to style anything on the page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleSheetCoverage {
+    pub style_sheet_id: String,
+    /// The URL the stylesheet was loaded from, or the page's URL for `<style>` elements
+    pub url: String,
+    pub is_inline: bool,
+    pub text: String,
+    /// Byte ranges of `text` taken up by used rules, in order and not overlapping
+    pub used: Vec<Range<usize>>,
+    /// Byte ranges of `text` taken up by unused rules, in order and not overlapping
+    pub unused: Vec<Range<usize>>,
+}
+
+impl StyleSheetCoverage {
+    /// Maps the offsets of `rules` (which should all be in this stylesheet) to byte ranges of
+    /// `text`.
+    pub fn new(
+        style_sheet_id: String,
+        url: String,
+        is_inline: bool,
+        text: String,
+        rules: &[RuleUsage],
+    ) -> Self {
+        // Chrome counts offsets in UTF-16 code units
+        let mut byte_offsets = Vec::with_capacity(text.len() + 1);
+        for (byte_offset, c) in text.char_indices() {
+            byte_offsets.extend(std::iter::repeat(byte_offset).take(c.len_utf16()));
+        }
+        byte_offsets.push(text.len());
+        // offsets are whole numbers, and `as` saturates anything out of range, which `min` then
+        // brings back within the text
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let to_bytes =
+            |offset: f64| byte_offsets[(offset.round() as usize).min(byte_offsets.len() - 1)];
+
+        let ranges = |used: bool| {
+            merge_ranges(
+                rules
+                    .iter()
+                    .filter(|rule| rule.used == used)
+                    .map(|rule| to_bytes(rule.start_offset)..to_bytes(rule.end_offset))
+                    .collect(),
+            )
+        };
+        let used = ranges(true);
+        // rules nested in an unused @media rule can still be used themselves
+        let unused = subtract_ranges(&ranges(false), &used);
+
+        Self {
+            style_sheet_id,
+            url,
+            is_inline,
+            text,
+            used,
+            unused,
+        }
+    }
+
+    pub fn used_bytes(&self) -> usize {
+        self.used.iter().map(|range| range.end - range.start).sum()
+    }
+
+    pub fn unused_bytes(&self) -> usize {
+        self.unused
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+
+    /// The text of the used rules, e.g. for inlining the critical CSS of a page.
+    pub fn used_text(&self) -> String {
+        self.used
+            .iter()
+            .map(|range| &self.text[range.clone()])
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Sorts `ranges` and merges the ones that overlap or touch.
+fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.retain(|range| range.start < range.end);
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = vec![];
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// The parts of `ranges` not covered by `holes`, both of which have to be merged already.
+fn subtract_ranges(ranges: &[Range<usize>], holes: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut remaining = vec![];
+    for range in ranges {
+        let mut start = range.start;
+        for hole in holes {
+            if hole.end <= start || hole.start >= range.end {
+                continue;
+            }
+            if hole.start > start {
+                remaining.push(start..hole.start);
+            }
+            start = hole.end;
+        }
+        if start < range.end {
+            remaining.push(start..range.end);
+        }
+    }
+    remaining
+}
+
+/// Offsets of a line in the source, in UTF-16 code units
+struct Line {
+    start: u32,
+    /// Where the first and last non-whitespace characters start and end
+    content_start: u32,
+    content_end: u32,
+}
+
+struct Lines {
+    lines: Vec<Line>,
+}
+
+impl Lines {
+    fn new(source: &str) -> Self {
+        let mut lines = vec![];
+        let mut line = Line {
+            start: 0,
+            content_start: 0,
+            content_end: 0,
+        };
+        let mut has_content = false;
+        let mut offset = 0;
+        for c in source.chars() {
+            let next = offset + saturating_u32(c.len_utf16());
+            if c == '\n' {
+                lines.push(line);
+                line = Line {
+                    start: next,
+                    content_start: next,
+                    content_end: next,
+                };
+                has_content = false;
+            } else if !c.is_whitespace() {
+                if !has_content {
+                    line.content_start = offset;
+                    has_content = true;
+                }
+                line.content_end = next;
+            }
+            offset = next;
+        }
+        lines.push(line);
+        Self { lines }
+    }
+
+    /// The index of the line `offset` is on
+    fn index_of(&self, offset: u32) -> usize {
+        match self.lines.binary_search_by_key(&offset, |line| line.start) {
+            Ok(index) => index,
+            Err(index) => index - 1,
+        }
+    }
+
+    fn position_of(&self, offset: u32) -> Position {
+        let index = self.index_of(offset);
+        Position {
+            line: saturating_u32(index + 1),
+            column: offset - self.lines[index].start,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::profiler::FunctionCoverage;
+
+    fn range(start_offset: u32, end_offset: u32, count: u32) -> CoverageRange {
+        CoverageRange {
+            start_offset,
+            end_offset,
+            count,
+        }
+    }
+
+    fn function(name: &str, ranges: Vec<CoverageRange>) -> FunctionCoverage {
+        FunctionCoverage {
+            function_name: name.to_string(),
+            ranges,
+        }
+    }
+
+    // the first of the coverage fixtures, after loading the page
+    const SOURCE: &str = include_str!("../../../tests/coverage_fixtures/coverage_fixture1.js");
+
+    fn fixture_coverage() -> ScriptCoverage {
+        ScriptCoverage {
+            script_id: "1".to_string(),
+            url: "http://127.0.0.1/coverage_fixture1.js".to_string(),
+            functions: vec![
+                function("", vec![range(0, 166, 1)]),
+                function("window.onload", vec![range(16, 53, 1)]),
+                function("button.onclick", vec![range(125, 163, 0)]),
+            ],
+        }
+    }
+
+    #[test]
+    fn maps_offsets_to_lines_and_columns() {
+        let lines = Lines::new("a\r\n  é😀b\n\n");
+        assert_eq!(Position { line: 1, column: 0 }, lines.position_of(0));
+        assert_eq!(Position { line: 2, column: 2 }, lines.position_of(5));
+        // the emoji is two UTF-16 code units long
+        assert_eq!(Position { line: 2, column: 6 }, lines.position_of(9));
+        assert_eq!(Position { line: 4, column: 0 }, lines.position_of(11));
+        assert_eq!(
+            (5, 9),
+            (lines.lines[1].content_start, lines.lines[1].content_end)
+        );
+    }
+
+    #[test]
+    fn inner_ranges_override_outer_ones() {
+        let source = "function f(x) {\n  if (x) {\n    return 1;\n  }\n  return 2;\n}\nf(1);\n";
+        let coverage = ScriptCoverage {
+            script_id: "1".to_string(),
+            url: "f.js".to_string(),
+            functions: vec![
+                function("", vec![range(0, 65, 1)]),
+                function("f", vec![range(0, 58, 3), range(44, 57, 0)]),
+            ],
+        };
+        let report = ScriptReport::new(&coverage, source);
+        let counts: Vec<(u32, u32)> = report
+            .lines
+            .iter()
+            .map(|line| (line.line, line.count))
+            .collect();
+        assert_eq!(
+            vec![(1, 3), (2, 3), (3, 3), (4, 3), (5, 0), (6, 3), (7, 1)],
+            counts
+        );
+        assert_eq!(1, report.functions.len());
+        assert_eq!(
+            BranchReport {
+                start: Position { line: 4, column: 3 },
+                end: Position { line: 6, column: 0 },
+                count: 0,
+            },
+            report.branches[0]
+        );
+    }
+
+    #[test]
+    fn writes_lcov() {
+        let report = ScriptReport::new(&fixture_coverage(), SOURCE);
+        let lcov = to_lcov(&[report]);
+        assert!(lcov.starts_with("TN:\nSF:http://127.0.0.1/coverage_fixture1.js\n"));
+        assert!(lcov.contains("FN:1,window.onload\nFN:5,button.onclick\n"));
+        assert!(lcov.contains("FNDA:1,window.onload\nFNDA:0,button.onclick\n"));
+        assert!(lcov.contains("FNF:2\nFNH:1\nBRF:0\nBRH:0\n"));
+        assert!(lcov.contains("DA:1,1\nDA:2,1\nDA:3,1\nDA:4,1\nDA:5,1\nDA:6,0\nDA:7,1\n"));
+        assert!(lcov.contains("LF:7\nLH:6\n"));
+        assert!(lcov.ends_with("end_of_record\n"));
+
+        let position = Position { line: 1, column: 0 };
+        let report = ScriptReport {
+            url: "f.js".to_string(),
+            lines: vec![],
+            functions: vec![FunctionReport {
+                name: "[a, b]\nc".to_string(),
+                start: position,
+                end: position,
+                count: 2,
+            }],
+            branches: vec![],
+        };
+        let lcov = to_lcov(&[report]);
+        assert!(lcov.contains("FN:1,[a_ b]_c\nFNDA:2,[a_ b]_c\n"));
+    }
+
+    #[test]
+    fn writes_istanbul_json() {
+        let report = ScriptReport::new(&fixture_coverage(), SOURCE);
+        let json = to_istanbul(&[report]);
+        let file = &json["http://127.0.0.1/coverage_fixture1.js"];
+        assert_eq!("button.onclick", file["fnMap"]["1"]["name"]);
+        assert_eq!(5, file["fnMap"]["1"]["line"]);
+        assert_eq!(0, file["f"]["1"]);
+        assert_eq!(
+            json!({ "start": { "line": 2, "column": 4 }, "end": { "line": 2, "column": 22 } }),
+            file["statementMap"]["1"]
+        );
+        assert_eq!(7, file["s"].as_object().unwrap().len());
+        assert_eq!(0, file["s"]["5"]);
+    }
+
+    #[test]
+    fn maps_css_rules_to_byte_ranges() {
+        let text = ".a { content: \"é\" }\n@media print { .b { color: red } .c { color: blue } }\n";
+        let rule = |start_offset: f64, end_offset: f64, used: bool| RuleUsage {
+            style_sheet_id: "1".to_string(),
+            start_offset,
+            end_offset,
+            used,
+        };
+        let coverage = StyleSheetCoverage::new(
+            "1".to_string(),
+            "https://example.com/style.css".to_string(),
+            false,
+            text.to_string(),
+            &[
+                rule(0.0, 19.0, true),
+                rule(20.0, 73.0, false),
+                rule(35.0, 52.0, true),
+            ],
+        );
+        // "é" takes up two bytes but only one UTF-16 code unit
+        assert_eq!(vec![0..20, 36..53], coverage.used);
+        assert_eq!(vec![21..36, 53..74], coverage.unused);
+        assert_eq!(
+            ".a { content: \"é\" }\n.b { color: red }",
+            coverage.used_text()
+        );
+        assert_eq!((37, 36), (coverage.used_bytes(), coverage.unused_bytes()));
+    }
+
+    #[test]
+    fn matches_url_patterns() {
+        assert!(url_matches("*", "https://example.com/app.js"));
+        assert!(url_matches(
+            "https://example.com/*",
+            "https://example.com/app.js"
+        ));
+        assert!(url_matches("*.js", "https://example.com/app.js"));
+        assert!(url_matches("*/app?js", "https://example.com/app.js"));
+        assert!(url_matches("*example*app*", "https://example.com/app.js"));
+        assert!(!url_matches("*.css", "https://example.com/app.js"));
+        assert!(!url_matches(
+            "https://example.com/",
+            "https://example.com/app.js"
+        ));
+    }
+}
@@ -1,12 +1,145 @@
-use failure::Error;
+use failure::{Error, Fail};
 use log::*;
+use serde::de::DeserializeOwned;
 
+use super::mouse::MouseButton;
 use super::point::Point;
 use crate::protocol::dom;
 use crate::protocol::page;
 use crate::protocol::runtime;
+use crate::protocol::RemoteError;
+use crate::util;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::time::Duration;
+
+/// `Node.nodeType` of elements, as opposed to text, comments, etc.
+const ELEMENT_NODE: u8 = 1;
+
+/// How long `Element::click` and friends wait for the element to become clickable
+const ACTIONABILITY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns why the element can't be scrolled to, or null.
+const CHECK_ATTACHED_AND_VISIBLE: &str = r#"function() {
+    if (!this.isConnected) {
+        return "it isn't attached to the document";
+    }
+    const style = window.getComputedStyle(this);
+    const rect = this.getBoundingClientRect();
+    if (style.visibility !== "visible" || rect.width === 0 || rect.height === 0) {
+        return "it isn't visible";
+    }
+    return null;
+}"#;
+
+/// JS expression that's true when `this` is disabled, directly, through a disabled fieldset or
+/// with `aria-disabled`. A macro so that it can be `concat!`ed into the functions below.
+macro_rules! is_disabled_js {
+    () => {
+        r#"(this.disabled === true || this.closest("fieldset:disabled") !== null
+        || this.getAttribute("aria-disabled") === "true")"#
+    };
+}
+
+const IS_ENABLED: &str = concat!("function() {\n    return !", is_disabled_js!(), ";\n}");
+
+/// Returns why the element can't be clicked, or null. Expects the element to have been
+/// scrolled into view.
+const CHECK_CLICKABLE: &str = concat!(
+    r##"async function() {
+    const nextFrame = () => new Promise(resolve => requestAnimationFrame(resolve));
+    await nextFrame();
+    const before = this.getBoundingClientRect();
+    await nextFrame();
+    const after = this.getBoundingClientRect();
+    if (before.x !== after.x || before.y !== after.y
+        || before.width !== after.width || before.height !== after.height) {
+        return "it's still moving";
+    }
+
+    if "##,
+    is_disabled_js!(),
+    r##" {
+        return "it's disabled";
+    }
+
+    const root = this.getRootNode();
+    const hit = (root.elementFromPoint ? root : document).elementFromPoint(
+        after.x + after.width / 2, after.y + after.height / 2);
+    if (hit === null) {
+        return "its middle is outside of the viewport";
+    }
+    if (hit !== this && !this.contains(hit)) {
+        const describe = node => node.tagName.toLowerCase()
+            + (node.id ? "#" + node.id : "")
+            + (typeof node.className === "string" && node.className
+                ? "." + node.className.trim().split(/\s+/).join(".") : "");
+        return "it's covered by " + describe(hit);
+    }
+    return null;
+}"##
+);
+
+#[derive(Debug, Fail)]
+#[fail(display = "No option with value or label {:?}", value)]
+pub struct OptionNotFound {
+    pub value: String,
+}
+
+#[derive(Debug, Fail)]
+#[fail(display = "Can't {} a {} element", action, node_name)]
+pub struct UnsupportedElement {
+    pub action: &'static str,
+    pub node_name: String,
+}
+
+/// Selects the options matching the given values or labels, returning the first that didn't
+/// match anything (or null). Nothing is changed unless every value matches.
+const SELECT_OPTIONS: &str = r#"function(values) {
+    const options = Array.from(this.options);
+    const selected = [];
+    for (const value of values) {
+        const option = options.find(o => o.value === value)
+            || options.find(o => o.label === value || o.text.trim() === value);
+        if (option === undefined) {
+            return value;
+        }
+        selected.push(option);
+    }
+    for (const option of options) {
+        option.selected = false;
+    }
+    for (const option of this.multiple ? selected : selected.slice(0, 1)) {
+        option.selected = true;
+    }
+    this.dispatchEvent(new Event("input", { bubbles: true }));
+    this.dispatchEvent(new Event("change", { bubbles: true }));
+    return null;
+}"#;
+
+#[derive(Debug, Fail)]
+#[fail(
+    display = "Element {} wasn't ready to be clicked after {:?}: {}",
+    backend_node_id, timeout, reason
+)]
+pub struct ElementNotActionable {
+    pub backend_node_id: dom::NodeId,
+    pub timeout: Duration,
+    /// Why the element wasn't clickable the last time we checked
+    pub reason: String,
+}
+
+/// Where to look for elements relative to an anchor element, see `Tab::find_elements_near`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    Above,
+    Below,
+    LeftOf,
+    RightOf,
+    /// In any direction, including overlapping the anchor
+    Any,
+}
 
 #[derive(Debug, Copy, Clone)]
 pub struct ElementQuad {
@@ -145,6 +278,71 @@ impl ElementQuad {
     pub fn within_bounds_of(&self, other: &Self) -> bool {
         self.within_horizontal_bounds_of(&other) && self.within_vertical_bounds_of(&other)
     }
+
+    /// If `self` lies entirely in `direction` from `other`, e.g. `RightOf` means `self` starts
+    /// at or after the point where `other` ends horizontally, at any height.
+    pub fn in_direction_of(&self, direction: Direction, other: &Self) -> bool {
+        match direction {
+            Direction::Above => self.above(other),
+            Direction::Below => self.below(other),
+            Direction::LeftOf => self.left_of(other),
+            Direction::RightOf => self.right_of(other),
+            Direction::Any => true,
+        }
+    }
+
+    /// The length of the shortest line between the edges of `self` and `other`, which is 0 if
+    /// they overlap.
+    pub fn distance_to(&self, other: &Self) -> f64 {
+        let dx = (other.most_left() - self.most_right())
+            .max(self.most_left() - other.most_right())
+            .max(0.0);
+        let dy = (other.most_top() - self.most_bottom())
+            .max(self.most_top() - other.most_bottom())
+            .max(0.0);
+        dx.hypot(dy)
+    }
+}
+
+/// The order in which someone reading a left-to-right page would come across the given quads,
+/// as indices into `quads`: row by row from the top, and from left to right within each row.
+/// A quad is on the same row as the previous one if it starts above the middle of that row's
+/// first quad.
+pub fn reading_order(quads: &[ElementQuad]) -> Vec<usize> {
+    let mut by_top: Vec<usize> = (0..quads.len()).collect();
+    by_top.sort_by(|&a, &b| {
+        quads[a]
+            .most_top()
+            .partial_cmp(&quads[b].most_top())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let mut order = Vec::with_capacity(quads.len());
+    let mut row: Vec<usize> = vec![];
+    let mut row_middle = 0.0;
+    for index in by_top {
+        let quad = &quads[index];
+        if !row.is_empty() && quad.most_top() >= row_middle {
+            sort_row(&mut row, quads);
+            order.append(&mut row);
+        }
+        if row.is_empty() {
+            row_middle = quad.most_top() + (quad.most_bottom() - quad.most_top()) / 2.0;
+        }
+        row.push(index);
+    }
+    sort_row(&mut row, quads);
+    order.append(&mut row);
+    order
+}
+
+fn sort_row(row: &mut [usize], quads: &[ElementQuad]) {
+    row.sort_by(|&a, &b| {
+        quads[a]
+            .most_left()
+            .partial_cmp(&quads[b].most_left())
+            .unwrap_or(Ordering::Equal)
+    });
 }
 
 #[derive(Debug, Clone)]
@@ -226,7 +424,13 @@ impl<'a> Element<'a> {
         }
 
         let backend_node_id = parent.describe_node(node_id)?.backend_node_id;
+        Self::from_backend_node_id(parent, backend_node_id)
+    }
 
+    fn from_backend_node_id(
+        parent: &'a super::Tab,
+        backend_node_id: dom::NodeId,
+    ) -> Result<Self, Error> {
         let remote_object_id = {
             let object = parent
                 .call_method(dom::methods::ResolveNode {
@@ -250,13 +454,226 @@ impl<'a> Element<'a> {
         Ok(self)
     }
 
+    /// Moves the mouse over this element, triggering `mouseover` and `:hover` styles
+    pub fn hover(&self) -> Result<&Self, Error> {
+        self.move_mouse_over()
+    }
+
+    /// Clicks the middle of this element, once it's actionable (see `wait_until_actionable`)
     pub fn click(&self) -> Result<&Self, Error> {
         debug!("Clicking element {:?}", &self);
+        self.wait_until_actionable()?;
         let midpoint = self.get_midpoint()?;
         self.parent.click_point(midpoint)?;
         Ok(self)
     }
 
+    pub fn double_click(&self) -> Result<&Self, Error> {
+        debug!("Double clicking element {:?}", &self);
+        self.wait_until_actionable()?;
+        let midpoint = self.get_midpoint()?;
+        self.parent.mouse().click(midpoint, MouseButton::Left, 2)?;
+        Ok(self)
+    }
+
+    /// Clicks this element with the right mouse button, which usually opens a context menu
+    pub fn right_click(&self) -> Result<&Self, Error> {
+        debug!("Right clicking element {:?}", &self);
+        self.wait_until_actionable()?;
+        let midpoint = self.get_midpoint()?;
+        self.parent.mouse().click(midpoint, MouseButton::Right, 1)?;
+        Ok(self)
+    }
+
+    /// Drags this element onto `target` with the left mouse button, see `Mouse::drag_and_drop`
+    pub fn drag_to(&self, target: &Element) -> Result<&Self, Error> {
+        debug!("Dragging element {:?} to {:?}", &self, target);
+        self.wait_until_actionable()?;
+        let from = self.get_midpoint()?;
+        let to = target.get_midpoint()?;
+        self.parent.mouse().drag_and_drop(from, to, 10)?;
+        Ok(self)
+    }
+
+    /// Taps the middle of this element, like a finger on a touch screen
+    pub fn tap(&self) -> Result<&Self, Error> {
+        debug!("Tapping element {:?}", &self);
+        self.wait_until_actionable()?;
+        let midpoint = self.get_midpoint()?;
+        self.parent.tap_point(midpoint)?;
+        Ok(self)
+    }
+
+    /// Waits until this element can receive a click: it must be attached to the document,
+    /// visible, not moving (its box is the same over two animation frames), enabled, and not
+    /// covered by another element at its middle. It's scrolled into view if necessary.
+    ///
+    /// Fails with `ElementNotActionable`, saying which check failed, after 10 seconds.
+    pub fn wait_until_actionable(&self) -> Result<&Self, Error> {
+        let mut reason = String::new();
+        let outcome = util::Wait::with_timeout(ACTIONABILITY_TIMEOUT).until(|| {
+            match self.check_actionable() {
+                Ok(None) => Some(Ok(())),
+                Ok(Some(problem)) => {
+                    trace!("{:?} isn't actionable yet: {}", self, problem);
+                    reason = problem;
+                    None
+                }
+                Err(error) => Some(Err(error)),
+            }
+        });
+        match outcome {
+            Ok(result) => result.map(|_| self),
+            Err(_timeout) => Err(ElementNotActionable {
+                backend_node_id: self.backend_node_id,
+                timeout: ACTIONABILITY_TIMEOUT,
+                reason,
+            }
+            .into()),
+        }
+    }
+
+    /// Scrolls the page so that this element is visible, if it isn't already
+    pub fn scroll_into_view(&self) -> Result<&Self, Error> {
+        let result = self
+            .parent
+            .call_method(dom::methods::ScrollIntoViewIfNeeded {
+                node_id: None,
+                backend_node_id: Some(self.backend_node_id),
+                object_id: None,
+            });
+        if let Err(error) = result {
+            match error.downcast_ref::<RemoteError>() {
+                Some(remote_error) if remote_error.is_method_not_found() => {
+                    self.call_js_fn(
+                        "function() { this.scrollIntoView({block: 'center', inline: 'center'}); }",
+                        false,
+                    )?;
+                }
+                _ => return Err(error),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Returns why this element can't be clicked right now, if it can't.
+    fn check_actionable(&self) -> Result<Option<String>, Error> {
+        if let Some(problem) =
+            self.call_js_fn_by_value(CHECK_ATTACHED_AND_VISIBLE, vec![], false)?
+        {
+            return Ok(Some(problem));
+        }
+        self.scroll_into_view()?;
+        self.call_js_fn_by_value(CHECK_CLICKABLE, vec![], true)
+    }
+
+    /// Calls a function with the given arguments, and deserializes its (JSON-serializable)
+    /// return value
+    fn call_js_fn_by_value<T>(
+        &self,
+        function_declaration: &str,
+        arguments: Vec<serde_json::Value>,
+        await_promise: bool,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let arguments = arguments
+            .into_iter()
+            .map(|value| runtime::methods::CallArgument {
+                value: Some(value),
+                ..Default::default()
+            })
+            .collect();
+        let result = self
+            .parent
+            .call_method(runtime::methods::CallFunctionOn {
+                object_id: &self.remote_object_id,
+                function_declaration,
+                arguments: Some(arguments),
+                return_by_value: true,
+                generate_preview: false,
+                silent: false,
+                await_promise,
+            })?
+            .result;
+        Ok(serde_json::from_value(
+            result.value.unwrap_or(serde_json::Value::Null),
+        )?)
+    }
+
+    /// The rendered text of this element, as in `HTMLElement.innerText`
+    pub fn get_inner_text(&self) -> Result<String, Error> {
+        self.get_property("innerText")
+    }
+
+    pub fn get_inner_html(&self) -> Result<String, Error> {
+        self.get_property("innerHTML")
+    }
+
+    pub fn get_outer_html(&self) -> Result<String, Error> {
+        self.get_property("outerHTML")
+    }
+
+    /// The current value of an input, textarea or select element
+    pub fn get_value(&self) -> Result<String, Error> {
+        self.get_property("value")
+    }
+
+    /// Reads a property of the DOM object for this element, e.g. "checked" or "dataset".
+    ///
+    /// Missing properties are `undefined`, so use an `Option` for `T` if the property might
+    /// not exist.
+    pub fn get_property<T>(&self, name: &str) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        self.call_js_fn_by_value(
+            "function(name) { return this[name]; }",
+            vec![name.into()],
+            false,
+        )
+    }
+
+    /// Whether this element is attached to the document, has a non-empty box and isn't hidden
+    /// with `visibility`
+    pub fn is_visible(&self) -> Result<bool, Error> {
+        let problem: Option<String> =
+            self.call_js_fn_by_value(CHECK_ATTACHED_AND_VISIBLE, vec![], false)?;
+        Ok(problem.is_none())
+    }
+
+    /// Whether this checkbox or radio button is checked (or `aria-checked`)
+    pub fn is_checked(&self) -> Result<bool, Error> {
+        self.call_js_fn_by_value(
+            "function() { return this.checked === true || this.getAttribute('aria-checked') === 'true'; }",
+            vec![],
+            false,
+        )
+    }
+
+    /// Whether this element is enabled, i.e. not disabled itself, by a disabled fieldset or
+    /// with `aria-disabled`
+    pub fn is_enabled(&self) -> Result<bool, Error> {
+        self.call_js_fn_by_value(IS_ENABLED, vec![], false)
+    }
+
+    /// The computed value of a CSS property, e.g. "display" or "background-color"
+    pub fn get_computed_style(&self, property: &str) -> Result<String, Error> {
+        self.call_js_fn_by_value(
+            "function(property) { return window.getComputedStyle(this).getPropertyValue(property); }",
+            vec![property.into()],
+            false,
+        )
+    }
+
+    /// The value of one of this element's attributes, if it has it
+    pub fn get_attribute(&self, name: &str) -> Result<Option<String>, Error> {
+        Ok(self
+            .get_attributes()?
+            .and_then(|mut attributes| attributes.remove(name)))
+    }
+
     pub fn type_into(&self, text: &str) -> Result<&Self, Error> {
         self.click()?;
 
@@ -277,6 +694,7 @@ impl<'a> Element<'a> {
             .call_method(runtime::methods::CallFunctionOn {
                 object_id: &self.remote_object_id,
                 function_declaration,
+                arguments: None,
                 return_by_value: false,
                 generate_preview: true,
                 silent: false,
@@ -287,6 +705,150 @@ impl<'a> Element<'a> {
         Ok(result)
     }
 
+    /// Finds the first element matching `selector` among this element's descendants.
+    ///
+    /// ```rust,no_run
+    /// # use failure::Error;
+    /// # fn main() -> Result<(), Error> {
+    /// #
+    /// use headless_chrome::{Browser, LaunchOptionsBuilder};
+    /// let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+    /// let tab = browser.wait_for_initial_tab()?;
+    ///
+    /// let card = tab.wait_for_element(".card:nth-child(3)")?;
+    /// card.find_element("button.buy")?.click()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn find_element(&self, selector: &str) -> Result<Element<'a>, Error> {
+        trace!(
+            "Looking up element via selector: {} in {:?}",
+            selector,
+            self
+        );
+        let node_id = self
+            .parent
+            .call_method(dom::methods::QuerySelector {
+                node_id: self.get_node_id()?,
+                selector,
+            })?
+            .node_id;
+        Element::new(self.parent, node_id)
+    }
+
+    /// Finds every element matching `selector` among this element's descendants.
+    pub fn find_elements(&self, selector: &str) -> Result<Vec<Element<'a>>, Error> {
+        trace!(
+            "Looking up elements via selector: {} in {:?}",
+            selector,
+            self
+        );
+        let node_ids = self
+            .parent
+            .call_method(dom::methods::QuerySelectorAll {
+                node_id: self.get_node_id()?,
+                selector,
+            })?
+            .node_ids;
+
+        if node_ids.is_empty() {
+            return Err(super::NoElementFound {}.into());
+        }
+
+        node_ids
+            .into_iter()
+            .map(|node_id| Element::new(self.parent, node_id))
+            .collect()
+    }
+
+    /// The element containing this one, or `None` for the root element
+    pub fn parent(&self) -> Result<Option<Element<'a>>, Error> {
+        self.find_related_element("function() { return this.parentElement; }", vec![])
+    }
+
+    /// The elements directly inside this one, ignoring text and comment nodes
+    pub fn children(&self) -> Result<Vec<Element<'a>>, Error> {
+        let node = self
+            .parent
+            .call_method(dom::methods::DescribeNode {
+                node_id: None,
+                backend_node_id: Some(self.backend_node_id),
+                depth: Some(1),
+            })?
+            .node;
+
+        node.children
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|child| child.node_type == ELEMENT_NODE)
+            .map(|child| Element::from_backend_node_id(self.parent, child.backend_node_id))
+            .collect()
+    }
+
+    /// The element following this one in its parent, ignoring text and comment nodes
+    pub fn next_sibling(&self) -> Result<Option<Element<'a>>, Error> {
+        self.find_related_element("function() { return this.nextElementSibling; }", vec![])
+    }
+
+    /// The nearest element matching `selector`, starting with this element itself and going up
+    /// through its ancestors, as in `Element.closest` in JavaScript.
+    pub fn closest(&self, selector: &str) -> Result<Option<Element<'a>>, Error> {
+        self.find_related_element(
+            "function(selector) { return this.closest(selector); }",
+            vec![selector.into()],
+        )
+    }
+
+    /// The (unstable) node id of this element, which DOM methods like `QuerySelector` take.
+    fn get_node_id(&self) -> Result<dom::NodeId, Error> {
+        self.request_node(&self.remote_object_id)
+    }
+
+    fn request_node(&self, object_id: &str) -> Result<dom::NodeId, Error> {
+        // node ids are only handed out once the document has been requested
+        self.parent.get_document()?;
+        Ok(self
+            .parent
+            .call_method(dom::methods::RequestNode { object_id })?
+            .node_id)
+    }
+
+    /// Calls a function that returns another element (or null).
+    fn find_related_element(
+        &self,
+        function_declaration: &str,
+        arguments: Vec<serde_json::Value>,
+    ) -> Result<Option<Element<'a>>, Error> {
+        let arguments = arguments
+            .into_iter()
+            .map(|value| runtime::methods::CallArgument {
+                value: Some(value),
+                ..Default::default()
+            })
+            .collect();
+        let result = self
+            .parent
+            .call_method(runtime::methods::CallFunctionOn {
+                object_id: &self.remote_object_id,
+                function_declaration,
+                arguments: Some(arguments),
+                return_by_value: false,
+                generate_preview: false,
+                silent: false,
+                await_promise: false,
+            })?
+            .result;
+
+        match result.object_id {
+            Some(object_id) => {
+                let node_id = self.request_node(&object_id)?;
+                Element::new(self.parent, node_id).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
     pub fn focus(&self) -> Result<&Self, Error> {
         self.parent.call_method(dom::methods::Focus {
             backend_node_id: Some(self.backend_node_id),
@@ -330,6 +892,63 @@ impl<'a> Element<'a> {
             .capture_screenshot(format, Some(self.get_box_model()?.content_viewport()), true)
     }
 
+    /// Selects the options of this `<select>` element whose value or label matches one of
+    /// `values`, and deselects the rest. Only the first is used unless it's a `multiple`
+    /// select. Fires `input` and `change` events, like a user would.
+    pub fn select_options(&self, values: &[&str]) -> Result<&Self, Error> {
+        debug!("Selecting {:?} in {:?}", values, self);
+        let node_name = self.get_property::<String>("nodeName")?;
+        if node_name != "SELECT" {
+            return Err(UnsupportedElement {
+                action: "select options of",
+                node_name,
+            }
+            .into());
+        }
+        let missing: Option<String> =
+            self.call_js_fn_by_value(SELECT_OPTIONS, vec![values.into()], false)?;
+        if let Some(value) = missing {
+            return Err(OptionNotFound { value }.into());
+        }
+        Ok(self)
+    }
+
+    /// Checks this checkbox or radio button by clicking it, unless it's already checked
+    pub fn check(&self) -> Result<&Self, Error> {
+        self.set_checked(true)
+    }
+
+    /// Unchecks this checkbox by clicking it, unless it's already unchecked
+    pub fn uncheck(&self) -> Result<&Self, Error> {
+        self.set_checked(false)
+    }
+
+    fn set_checked(&self, checked: bool) -> Result<&Self, Error> {
+        if self.is_checked()? != checked {
+            self.click()?;
+        }
+        Ok(self)
+    }
+
+    /// Empties this input, textarea or contenteditable element the way a user would: by
+    /// selecting its contents and pressing Backspace.
+    pub fn clear(&self) -> Result<&Self, Error> {
+        debug!("Clearing {:?}", self);
+        self.click()?;
+        self.call_js_fn(
+            "function() {
+                if (typeof this.select === 'function') {
+                    this.select();
+                } else {
+                    window.getSelection().selectAllChildren(this);
+                }
+            }",
+            false,
+        )?;
+        self.parent.press_key("Backspace")?;
+        Ok(self)
+    }
+
     pub fn set_input_files(&self, file_paths: &[&str]) -> Result<&Self, Error> {
         self.parent.call_method(dom::methods::SetFileInputFiles {
             files: file_paths,
@@ -400,3 +1019,57 @@ impl<'a> Element<'a> {
         Ok(midpoint)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad(left: f64, top: f64, width: f64, height: f64) -> ElementQuad {
+        ElementQuad::from_raw_points(&[
+            left,
+            top,
+            left + width,
+            top,
+            left + width,
+            top + height,
+            left,
+            top + height,
+        ])
+    }
+
+    #[test]
+    fn distances_and_directions() {
+        let label = quad(10.0, 10.0, 50.0, 20.0);
+        let input = quad(70.0, 12.0, 100.0, 20.0);
+        let below = quad(10.0, 60.0, 50.0, 20.0);
+
+        assert_eq!(10.0, input.distance_to(&label));
+        assert_eq!(10.0, label.distance_to(&input));
+        assert_eq!(30.0, below.distance_to(&label));
+        assert_eq!(
+            50.0,
+            quad(100.0, 70.0, 10.0, 10.0).distance_to(&quad(0.0, 0.0, 70.0, 30.0))
+        );
+        assert_eq!(0.0, quad(0.0, 0.0, 100.0, 100.0).distance_to(&label));
+
+        assert!(input.in_direction_of(Direction::RightOf, &label));
+        assert!(!input.in_direction_of(Direction::LeftOf, &label));
+        assert!(!input.in_direction_of(Direction::Below, &label));
+        assert!(below.in_direction_of(Direction::Below, &label));
+        assert!(label.in_direction_of(Direction::Above, &below));
+        assert!(below.in_direction_of(Direction::Any, &label));
+    }
+
+    #[test]
+    fn sorts_quads_in_reading_order() {
+        let quads = [
+            quad(200.0, 52.0, 50.0, 20.0), // second row, right
+            quad(10.0, 10.0, 50.0, 20.0),  // first row, left
+            quad(10.0, 50.0, 50.0, 20.0),  // second row, left
+            quad(100.0, 8.0, 50.0, 20.0),  // first row, right, slightly higher
+            quad(10.0, 100.0, 300.0, 5.0), // third row
+        ];
+        assert_eq!(vec![1, 3, 2, 0, 4], reading_order(&quads));
+        assert!(reading_order(&[]).is_empty());
+    }
+}
@@ -0,0 +1,313 @@
+use std::convert::TryFrom;
+use std::sync::atomic::Ordering;
+
+use failure::Error;
+use log::*;
+
+use super::keys::KeyDefinition;
+pub use super::keys::{KeyNotFoundError, KeyboardLayout};
+use super::Tab;
+use crate::protocol::{input, RemoteError};
+
+/// Bits of the `modifiers` field of input events.
+pub mod modifiers {
+    pub const ALT: u32 = 1;
+    pub const CONTROL: u32 = 2;
+    /// Command on macOS
+    pub const META: u32 = 4;
+    pub const SHIFT: u32 = 8;
+}
+
+/// Simulates key presses on a tab, keeping track of which modifier keys are held down so that
+/// they're applied to subsequent key (and mouse) events.
+///
+/// ```rust,no_run
+/// # use failure::Error;
+/// # fn main() -> Result<(), Error> {
+/// #
+/// use headless_chrome::{Browser, LaunchOptionsBuilder};
+/// let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+/// let tab = browser.wait_for_initial_tab()?;
+///
+/// // select everything in the focused input and replace it
+/// tab.keyboard().press_chord("Control+A")?;
+/// tab.type_str("replacement")?;
+///
+/// // go back to the previous field
+/// tab.keyboard().down("Shift")?.press("Tab")?.up("Shift")?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct Keyboard<'a> {
+    tab: &'a Tab,
+}
+
+impl<'a> Keyboard<'a> {
+    pub(crate) fn new(tab: &'a Tab) -> Self {
+        Self { tab }
+    }
+
+    /// The modifier keys currently held down, as a bit field of the `modifiers` constants.
+    pub fn modifiers(&self) -> u32 {
+        self.tab.keyboard_modifiers.load(Ordering::SeqCst)
+    }
+
+    /// Dispatches a keydown event for `key`, e.g. "a", "Enter" or "Shift".
+    ///
+    /// Modifier keys stay held until the corresponding `up` call. While Shift is held, keys
+    /// produce their shifted variant ("a" becomes "A", "1" becomes "!"), and while Control, Alt
+    /// or Meta are held, keys don't produce any text (so that e.g. Control+A selects everything
+    /// rather than typing "a").
+    pub fn down(&self, key: &str) -> Result<&Self, Error> {
+        let layout = self.tab.get_keyboard_layout();
+        let definition = layout.get_key_definition(key)?;
+        let modifiers = self
+            .tab
+            .keyboard_modifiers
+            .fetch_or(modifier_bit(&definition.key), Ordering::SeqCst)
+            | modifier_bit(&definition.key);
+        let description = describe(definition, modifiers);
+
+        // See https://github.com/GoogleChrome/puppeteer/blob/62da2366c65b335751896afbb0206f23c61436f1/lib/Input.js#L52
+        let event_type = if description.text.is_some() {
+            "keyDown"
+        } else {
+            "rawKeyDown"
+        };
+
+        trace!("Key down: {:?} (modifiers: {})", description.key, modifiers);
+        self.tab.call_method(input::methods::DispatchKeyEvent {
+            event_type,
+            key: Some(description.key),
+            text: description.text,
+            code: Some(&definition.code),
+            windows_virtual_key_code: definition.key_code,
+            native_virtual_key_code: definition.key_code,
+            modifiers: Some(modifiers),
+            location: Some(definition.location),
+        })?;
+        Ok(self)
+    }
+
+    /// Dispatches a keyup event for `key`, releasing it if it's a modifier.
+    pub fn up(&self, key: &str) -> Result<&Self, Error> {
+        let layout = self.tab.get_keyboard_layout();
+        let definition = layout.get_key_definition(key)?;
+        let modifiers = self
+            .tab
+            .keyboard_modifiers
+            .fetch_and(!modifier_bit(&definition.key), Ordering::SeqCst)
+            & !modifier_bit(&definition.key);
+        let description = describe(definition, modifiers);
+
+        trace!("Key up: {:?} (modifiers: {})", description.key, modifiers);
+        self.tab.call_method(input::methods::DispatchKeyEvent {
+            event_type: "keyUp",
+            key: Some(description.key),
+            text: description.text,
+            code: Some(&definition.code),
+            windows_virtual_key_code: definition.key_code,
+            native_virtual_key_code: definition.key_code,
+            modifiers: Some(modifiers),
+            location: Some(definition.location),
+        })?;
+        Ok(self)
+    }
+
+    /// Presses and releases `key`.
+    pub fn press(&self, key: &str) -> Result<&Self, Error> {
+        self.down(key)?;
+        self.up(key)
+    }
+
+    /// Presses a combination of keys separated by "+", like "Control+Shift+K" or "Meta+V".
+    ///
+    /// The keys are pressed in order and released in reverse order. Use "+" as the last key to
+    /// press the plus key itself, e.g. "Control++".
+    pub fn press_chord(&self, chord: &str) -> Result<&Self, Error> {
+        let keys = split_chord(chord);
+        debug!("Pressing chord: {:?}", keys);
+
+        let mut held = vec![];
+        let mut result = Ok(());
+        for key in &keys {
+            if let Err(error) = self.down(key) {
+                result = Err(error);
+                break;
+            }
+            held.push(key);
+        }
+        // release whatever we managed to press, even if one of the keys failed
+        for key in held.into_iter().rev() {
+            let released = self.up(key);
+            if result.is_ok() {
+                result = released.map(|_| ());
+            }
+        }
+        result.map(|_| self)
+    }
+
+    /// Types `text` by pressing the key for each character. Characters that have no key in the
+    /// layout (accented letters, emoji, CJK...) are inserted directly, as if by an IME.
+    pub fn type_str(&self, text: &str) -> Result<&Self, Error> {
+        let layout = self.tab.get_keyboard_layout();
+        let mut pending_insert = String::new();
+        for c in text.chars() {
+            let key = c.to_string();
+            if layout.get_key_definition(&key).is_ok() {
+                if !pending_insert.is_empty() {
+                    self.insert_text(&pending_insert)?;
+                    pending_insert.clear();
+                }
+                self.press(&key)?;
+            } else {
+                // insert runs of such characters in one go, so that e.g. emoji made up of
+                // several characters arrive intact
+                pending_insert.push(c);
+            }
+        }
+        if !pending_insert.is_empty() {
+            self.insert_text(&pending_insert)?;
+        }
+        Ok(self)
+    }
+
+    /// Inserts `text` into the focused element without dispatching any key events, like pasting
+    /// or committing an IME composition. Only `input` events are fired.
+    pub fn insert_text(&self, text: &str) -> Result<&Self, Error> {
+        trace!("Inserting text: {:?}", text);
+        self.tab.call_method(input::methods::InsertText { text })?;
+        Ok(self)
+    }
+
+    /// Starts or updates an IME composition with `text`, which is shown as uncommitted text in
+    /// the focused element and fires `compositionstart` / `compositionupdate` events. The
+    /// selection is given in UTF-16 code units from the start of `text`.
+    ///
+    /// Follow it with `insert_text` to commit the composition.
+    pub fn set_composition(
+        &self,
+        text: &str,
+        selection_start: u32,
+        selection_end: u32,
+    ) -> Result<&Self, Error> {
+        self.tab.call_method(input::methods::ImeSetComposition {
+            text,
+            selection_start,
+            selection_end,
+            replacement_start: None,
+            replacement_end: None,
+        })?;
+        Ok(self)
+    }
+
+    /// Enters `text` the way an IME would: as a composition that is then committed, so that
+    /// composition events are fired. On versions of Chrome without `Input.imeSetComposition`,
+    /// the text is simply inserted.
+    pub fn compose(&self, text: &str) -> Result<&Self, Error> {
+        // the caret goes at the end, which Chrome counts in UTF-16 code units
+        let length = u32::try_from(text.encode_utf16().count())?;
+        if let Err(error) = self.set_composition(text, length, length) {
+            match error.downcast_ref::<RemoteError>() {
+                Some(remote_error) if remote_error.is_method_not_found() => {
+                    debug!("IME composition not supported, inserting text instead");
+                }
+                _ => return Err(error),
+            }
+        }
+        self.insert_text(text)
+    }
+}
+
+struct KeyDescription<'a> {
+    key: &'a str,
+    text: Option<&'a str>,
+}
+
+fn describe(definition: &KeyDefinition, modifiers: u32) -> KeyDescription<'_> {
+    let key: &str = if modifiers & modifiers::SHIFT != 0 {
+        definition.shift_key.as_ref().unwrap_or(&definition.key)
+    } else {
+        &definition.key
+    };
+
+    // See https://github.com/GoogleChrome/puppeteer/blob/62da2366c65b335751896afbb0206f23c61436f1/lib/Input.js#L114-L115
+    let text = if modifiers & (modifiers::ALT | modifiers::CONTROL | modifiers::META) != 0 {
+        None
+    } else if key != definition.key {
+        Some(key)
+    } else {
+        definition.text.as_ref().map(AsRef::as_ref).or_else(|| {
+            if key.chars().count() == 1 {
+                Some(key)
+            } else {
+                None
+            }
+        })
+    };
+
+    KeyDescription { key, text }
+}
+
+fn modifier_bit(key: &str) -> u32 {
+    match key {
+        "Alt" => modifiers::ALT,
+        "Control" => modifiers::CONTROL,
+        "Meta" => modifiers::META,
+        "Shift" => modifiers::SHIFT,
+        _ => 0,
+    }
+}
+
+fn split_chord(chord: &str) -> Vec<&str> {
+    let mut keys = vec![];
+    let mut parts = chord.split('+').peekable();
+    while let Some(part) = parts.next() {
+        if part.is_empty() {
+            // "Control++" splits into ["Control", "", ""]
+            if parts.peek() == Some(&"") {
+                parts.next();
+            }
+            keys.push("+");
+        } else {
+            keys.push(part);
+        }
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_chords() {
+        assert_eq!(
+            vec!["Control", "Shift", "K"],
+            split_chord("Control+Shift+K")
+        );
+        assert_eq!(vec!["Control", "+"], split_chord("Control++"));
+        assert_eq!(vec!["+"], split_chord("+"));
+        assert_eq!(vec!["Enter"], split_chord("Enter"));
+    }
+
+    #[test]
+    fn shift_changes_key_and_text() {
+        let layout = KeyboardLayout::us();
+        let a = layout.get_key_definition("a").unwrap();
+        let shifted = describe(a, modifiers::SHIFT);
+        assert_eq!("A", shifted.key);
+        assert_eq!(Some("A"), shifted.text);
+
+        let with_control = describe(a, modifiers::CONTROL);
+        assert_eq!("a", with_control.key);
+        assert_eq!(None, with_control.text);
+
+        let one = layout.get_key_definition("1").unwrap();
+        assert_eq!(Some("!"), describe(one, modifiers::SHIFT).text);
+
+        let enter = layout.get_key_definition("Enter").unwrap();
+        assert_eq!(Some("\r"), describe(enter, 0).text);
+    }
+}
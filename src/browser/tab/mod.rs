@@ -1,20 +1,27 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
 
-use failure::{Error, Fail};
+use failure::{format_err, Error, Fail};
 use log::*;
 use serde;
 
-use element::Element;
+use element::{Direction, Element};
+use keyboard::{Keyboard, KeyboardLayout};
+use mouse::{Mouse, MouseButton};
 use point::Point;
+use screencast::{ScreencastFormat, ScreencastFrame, ScreencastOptions};
 
 use crate::browser::Transport;
 use crate::protocol::page::methods::Navigate;
 use crate::protocol::target::TargetId;
 use crate::protocol::target::TargetInfo;
-use crate::protocol::{dom, input, page, profiler, target};
+use crate::protocol::{css, dom, emulation, input, inspector, io, page, profiler, target};
 use crate::protocol::{network, Event};
 use crate::{protocol, util};
 
@@ -22,9 +29,14 @@ use super::transport::SessionId;
 use crate::protocol::dom::Node;
 use std::time::Duration;
 
+pub mod coverage;
 pub mod element;
+pub mod keyboard;
 mod keys;
-mod point;
+pub mod mouse;
+pub mod point;
+pub mod screencast;
+pub mod screenshot;
 
 #[derive(Debug)]
 pub enum RequestInterceptionDecision {
@@ -43,6 +55,9 @@ pub type RequestInterceptor = Box<
         + Sync,
 >;
 
+/// How many bytes of a streamed PDF to ask Chrome for at a time
+const PDF_STREAM_CHUNK_SIZE: u32 = 1024 * 1024;
+
 /// A handle to a single page. Exposes methods for simulating user actions (clicking,
 /// typing), and also for getting information about the DOM and other parts of the page.
 pub struct Tab {
@@ -50,10 +65,26 @@ pub struct Tab {
     transport: Arc<Transport>,
     session_id: SessionId,
     navigating: Arc<AtomicBool>,
+    crashed: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+    keyboard_modifiers: AtomicU32,
+    keyboard_layout: Mutex<Arc<KeyboardLayout>>,
+    mouse_position: Mutex<Point>,
+    mouse_buttons: AtomicU32,
     target_info: Arc<Mutex<TargetInfo>>,
     request_interceptor: Arc<Mutex<RequestInterceptor>>,
+    screencast: Arc<Mutex<Option<ScreencastSink>>>,
+    /// Stylesheets on the page in the order they were added, as reported since the CSS domain
+    /// was last enabled
+    style_sheets: Arc<Mutex<Vec<css::StyleSheetHeader>>>,
+    /// The device metrics override set with `set_device_metrics_override`, if it hasn't been
+    /// cleared
+    device_metrics_override: Mutex<Option<emulation::methods::SetDeviceMetricsOverride>>,
 }
 
+/// Where the event handling thread sends screencast frames to
+type ScreencastSink = (mpsc::Sender<ScreencastFrame>, ScreencastFormat);
+
 #[derive(Debug, Fail)]
 #[fail(display = "No element found")]
 pub struct NoElementFound {}
@@ -64,6 +95,43 @@ pub struct NavigationFailed {
     error_text: String,
 }
 
+#[derive(Debug, Fail, Clone)]
+#[fail(display = "Tab {} crashed", target_id)]
+pub struct TabCrashed {
+    pub target_id: TargetId,
+}
+
+#[derive(Debug, Fail, Clone)]
+#[fail(display = "Tab {} has been closed", target_id)]
+pub struct TabClosed {
+    pub target_id: TargetId,
+}
+
+#[derive(Debug, Fail)]
+#[fail(display = "Chrome refused to close tab {}", target_id)]
+pub struct CloseFailed {
+    target_id: TargetId,
+}
+
+/// Shared by `Tab::mark_crashed` and the tab's event handling thread, which only has clones of
+/// the tab's fields.
+fn mark_crashed(
+    crashed: &AtomicBool,
+    transport: &Transport,
+    session_id: &SessionId,
+    target_id: &TargetId,
+) {
+    if !crashed.swap(true, Ordering::SeqCst) {
+        warn!("Tab {} crashed", target_id);
+        transport.cancel_calls_for_session(
+            session_id,
+            TabCrashed {
+                target_id: target_id.clone(),
+            },
+        );
+    }
+}
+
 impl<'a> Tab {
     pub fn new(target_info: TargetInfo, transport: Arc<Transport>) -> Result<Self, Error> {
         let target_id = target_info.target_id.clone();
@@ -85,14 +153,24 @@ impl<'a> Tab {
             transport,
             session_id,
             navigating: Arc::new(AtomicBool::new(false)),
+            crashed: Arc::new(AtomicBool::new(false)),
+            closed: Arc::new(AtomicBool::new(false)),
+            keyboard_modifiers: AtomicU32::new(0),
+            keyboard_layout: Mutex::new(Arc::new(KeyboardLayout::us())),
+            mouse_position: Mutex::new(Point { x: 0.0, y: 0.0 }),
+            mouse_buttons: AtomicU32::new(0),
             target_info: target_info_mutex,
             request_interceptor: Arc::new(Mutex::new(Box::new(
                 |_transport, _session_id, _interception| RequestInterceptionDecision::Continue,
             ))),
+            screencast: Arc::new(Mutex::new(None)),
+            style_sheets: Arc::new(Mutex::new(vec![])),
+            device_metrics_override: Mutex::new(None),
         };
 
         tab.call_method(page::methods::Enable {})?;
         tab.call_method(page::methods::SetLifecycleEventsEnabled { enabled: true })?;
+        tab.call_method(inspector::methods::Enable {})?;
 
         tab.start_event_handler_thread();
 
@@ -126,6 +204,41 @@ impl<'a> Tab {
         info.url.clone()
     }
 
+    /// Whether the renderer process for this tab crashed (or was killed, e.g. for running out
+    /// of memory). Method calls on a crashed tab fail with `TabCrashed`, but it can still be
+    /// closed, and it stays in `Browser::get_tabs` until it is.
+    pub fn is_crashed(&self) -> bool {
+        self.crashed.load(Ordering::SeqCst)
+    }
+
+    /// Whether this tab has been closed. Method calls on a closed tab fail with `TabClosed`.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn mark_crashed(&self) {
+        mark_crashed(
+            &self.crashed,
+            &self.transport,
+            &self.session_id,
+            &self.target_id,
+        );
+    }
+
+    pub(crate) fn mark_closed(&self) {
+        if !self.closed.swap(true, Ordering::SeqCst) {
+            debug!("Tab {} was closed", self.target_id);
+            self.transport
+                .stop_listening_to_target_events(&self.session_id);
+            self.transport.cancel_calls_for_session(
+                &self.session_id,
+                TabClosed {
+                    target_id: self.target_id.clone(),
+                },
+            );
+        }
+    }
+
     fn start_event_handler_thread(&self) {
         let transport: Arc<Transport> = Arc::clone(&self.transport);
         let incoming_events_rx = self
@@ -134,10 +247,17 @@ impl<'a> Tab {
         let navigating = Arc::clone(&self.navigating);
         let interceptor_mutex = Arc::clone(&self.request_interceptor);
         let session_id = self.session_id.clone();
+        let crashed = Arc::clone(&self.crashed);
+        let target_id = self.target_id.clone();
+        let screencast = Arc::clone(&self.screencast);
+        let style_sheets = Arc::clone(&self.style_sheets);
 
         thread::spawn(move || {
             for event in incoming_events_rx {
                 match event {
+                    Event::InspectorTargetCrashed(_) => {
+                        mark_crashed(&crashed, &transport, &session_id, &target_id);
+                    }
                     Event::Lifecycle(lifecycle_event) => {
                         match lifecycle_event.params.name.as_ref() {
                             "networkAlmostIdle" => {
@@ -149,6 +269,45 @@ impl<'a> Tab {
                             _ => {}
                         }
                     }
+                    Event::ScreencastFrame(frame_event) => {
+                        let params = frame_event.params;
+                        // Chrome doesn't send the next frame until this one is acknowledged
+                        let ack = page::methods::ScreencastFrameAck {
+                            session_id: params.session_id,
+                        };
+                        if let Err(error) = transport.call_method_on_target(session_id.clone(), ack)
+                        {
+                            warn!("Couldn't acknowledge screencast frame: {}", error);
+                        }
+                        if let Some((sender, format)) = screencast.lock().unwrap().as_ref() {
+                            match base64::decode(&params.data) {
+                                Ok(data) => {
+                                    let frame = ScreencastFrame {
+                                        data,
+                                        format: *format,
+                                        metadata: params.metadata,
+                                    };
+                                    if sender.send(frame).is_err() {
+                                        trace!("Screencast frame receiver was dropped");
+                                    }
+                                }
+                                Err(error) => warn!("Couldn't decode screencast frame: {}", error),
+                            }
+                        }
+                    }
+                    Event::StyleSheetAdded(style_sheet_event) => {
+                        style_sheets
+                            .lock()
+                            .unwrap()
+                            .push(style_sheet_event.params.header);
+                    }
+                    Event::StyleSheetRemoved(style_sheet_event) => {
+                        let removed_id = style_sheet_event.params.style_sheet_id;
+                        style_sheets
+                            .lock()
+                            .unwrap()
+                            .retain(|header| header.style_sheet_id != removed_id);
+                    }
                     Event::RequestIntercepted(interception_event) => {
                         let id = interception_event.params.interception_id.clone();
                         let interceptor = interceptor_mutex.lock().unwrap();
@@ -195,6 +354,18 @@ impl<'a> Tab {
         C: protocol::Method + serde::Serialize + std::fmt::Debug,
     {
         trace!("Calling method: {:?}", method);
+        if self.is_closed() {
+            return Err(TabClosed {
+                target_id: self.target_id.clone(),
+            }
+            .into());
+        }
+        if self.is_crashed() {
+            return Err(TabCrashed {
+                target_id: self.target_id.clone(),
+            }
+            .into());
+        }
         let result = self
             .transport
             .call_method_on_target(self.session_id.clone(), method);
@@ -240,6 +411,42 @@ impl<'a> Tab {
         Ok(self)
     }
 
+    /// Replaces the document in this tab with `html`, and waits until it (along with its images,
+    /// stylesheets and so on) has loaded.
+    ///
+    /// The tab's URL stays the same, so relative URLs in `html` are resolved against whatever
+    /// page the tab was showing, unless `html` has a `<base>` element.
+    pub fn set_content(&self, html: &str) -> Result<&Self, Error> {
+        let frame_id = self
+            .call_method(page::methods::GetFrameTree {})?
+            .frame_tree
+            .frame
+            .id;
+        self.call_method(page::methods::SetDocumentContent {
+            frame_id: &frame_id,
+            html,
+        })?;
+        debug!("Set content of frame {}, waiting for it to load", frame_id);
+
+        util::Wait::with_timeout(Duration::from_secs(20)).until(|| {
+            let ready_state = self
+                .call_method(protocol::runtime::methods::Evaluate {
+                    expression: "document.readyState",
+                    return_by_value: true,
+                    await_promise: false,
+                })
+                .ok()?
+                .result
+                .value?;
+            if ready_state == "complete" {
+                Some(())
+            } else {
+                None
+            }
+        })?;
+        Ok(self)
+    }
+
     pub fn wait_for_element(&self, selector: &str) -> Result<Element<'_>, Error> {
         self.wait_for_element_with_custom_timeout(selector, std::time::Duration::from_secs(3))
     }
@@ -299,6 +506,83 @@ impl<'a> Tab {
             .root)
     }
 
+    /// Fills in several form fields at once, from a map of CSS selectors to values. How each
+    /// value is entered depends on the element:
+    ///
+    /// * `<select>`: the option with that value or label is selected
+    /// * checkboxes and radio buttons: checked, unless the value is "false" or empty
+    /// * file inputs: the value is the path of the file to upload
+    /// * other inputs, textareas and contenteditable elements: cleared, then typed into
+    ///
+    /// ```rust,no_run
+    /// # use failure::Error;
+    /// # fn main() -> Result<(), Error> {
+    /// #
+    /// use std::collections::HashMap;
+    /// use headless_chrome::{Browser, LaunchOptionsBuilder};
+    /// let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+    /// let tab = browser.wait_for_initial_tab()?;
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("#name", "Laika");
+    /// values.insert("#species", "Dog");
+    /// values.insert("#good", "true");
+    /// tab.fill_form(values)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fill_form(&self, values: HashMap<&str, &str>) -> Result<&Self, Error> {
+        for (selector, value) in values {
+            let element = self.wait_for_element(selector)?;
+            let node = element.get_description()?;
+            let attribute = |name: &str| {
+                node.attributes
+                    .as_ref()
+                    .and_then(|attributes| attributes.get(name))
+                    .map(|value| value.to_lowercase())
+            };
+            debug!(
+                "Filling in {} ({}) with {:?}",
+                selector, node.node_name, value
+            );
+
+            match (
+                node.node_name.as_ref(),
+                attribute("type").as_ref().map(String::as_str),
+            ) {
+                ("SELECT", _) => {
+                    element.select_options(&[value])?;
+                }
+                ("INPUT", Some("checkbox")) | ("INPUT", Some("radio")) => {
+                    if value.is_empty() || value == "false" {
+                        element.uncheck()?;
+                    } else {
+                        element.check()?;
+                    }
+                }
+                ("INPUT", Some("file")) => {
+                    element.set_input_files(&[value])?;
+                }
+                ("INPUT", _) | ("TEXTAREA", _) => {
+                    element.clear()?.type_into(value)?;
+                }
+                (node_name, _) => {
+                    if attribute("contenteditable").map_or(false, |editable| editable != "false") {
+                        element.clear()?.type_into(value)?;
+                    } else {
+                        return Err(element::UnsupportedElement {
+                            action: "fill in",
+                            node_name: node_name.to_string(),
+                        }
+                        .into());
+                    }
+                }
+            }
+        }
+        Ok(self)
+    }
+
     pub fn find_elements(&self, selector: &str) -> Result<Vec<Element<'_>>, Error> {
         trace!("Looking up elements via selector: {}", selector);
 
@@ -322,6 +606,103 @@ impl<'a> Tab {
             .collect()
     }
 
+    /// Finds the elements matching `selector` that lie in `direction` from `anchor`, closest
+    /// first, for targeting elements by where they are on the page rather than by their
+    /// attributes. Only elements within `max_distance` CSS pixels of the anchor (edge to edge)
+    /// are returned, if given. Elements that aren't rendered and the anchor itself are skipped,
+    /// as are elements whose box contains the anchor's when looking in `Direction::Any`.
+    ///
+    /// ```rust,no_run
+    /// # use failure::Error;
+    /// # fn main() -> Result<(), Error> {
+    /// #
+    /// use headless_chrome::{Browser, LaunchOptionsBuilder};
+    /// use headless_chrome::browser::tab::element::Direction;
+    /// let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+    /// let tab = browser.wait_for_initial_tab()?;
+    ///
+    /// // the input to the right of the "Email" label
+    /// let label = tab
+    ///     .find_elements("label")?
+    ///     .into_iter()
+    ///     .find(|label| label.get_inner_text().ok().as_ref().map(String::as_str) == Some("Email"))
+    ///     .unwrap();
+    /// let inputs = tab.find_elements_near("input", &label, Direction::RightOf, Some(50.0))?;
+    /// inputs[0].type_into("laika@example.com")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn find_elements_near(
+        &self,
+        selector: &str,
+        anchor: &Element,
+        direction: Direction,
+        max_distance: Option<f64>,
+    ) -> Result<Vec<Element<'_>>, Error> {
+        let anchor_quad = anchor.get_box_model()?.border;
+        let mut found = vec![];
+        for element in self.find_elements(selector)? {
+            if element.backend_node_id == anchor.backend_node_id {
+                continue;
+            }
+            let quad = match element.get_box_model() {
+                Ok(model) => model.border,
+                Err(_) => continue,
+            };
+            if direction == Direction::Any && anchor_quad.within_bounds_of(&quad) {
+                continue;
+            }
+            let distance = quad.distance_to(&anchor_quad);
+            if quad.in_direction_of(direction, &anchor_quad)
+                && max_distance.map_or(true, |max| distance <= max)
+            {
+                found.push((distance, element));
+            }
+        }
+        trace!(
+            "Found {} elements matching {} {:?} {:?}",
+            found.len(),
+            selector,
+            direction,
+            anchor
+        );
+
+        if found.is_empty() {
+            return Err(NoElementFound {}.into());
+        }
+        found.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(found.into_iter().map(|(_, element)| element).collect())
+    }
+
+    /// Like `find_elements`, but sorted in the order someone reading the page would see them:
+    /// row by row from the top, and from left to right within each row. Elements that aren't
+    /// rendered come last.
+    pub fn find_elements_in_reading_order(
+        &self,
+        selector: &str,
+    ) -> Result<Vec<Element<'_>>, Error> {
+        let mut rendered = vec![];
+        let mut quads = vec![];
+        let mut not_rendered = vec![];
+        for element in self.find_elements(selector)? {
+            match element.get_box_model() {
+                Ok(model) => {
+                    quads.push(model.border);
+                    rendered.push(Some(element));
+                }
+                Err(_) => not_rendered.push(element),
+            }
+        }
+
+        let mut sorted: Vec<Element> = element::reading_order(&quads)
+            .into_iter()
+            .filter_map(|index| rendered[index].take())
+            .collect();
+        sorted.append(&mut not_rendered);
+        Ok(sorted)
+    }
+
     pub fn describe_node(&self, node_id: dom::NodeId) -> Result<dom::Node, Error> {
         let node = self
             .call_method(dom::methods::DescribeNode {
@@ -333,56 +714,36 @@ impl<'a> Tab {
         Ok(node)
     }
 
+    /// Types `string_to_type` into the focused element, see `Keyboard::type_str`
     pub fn type_str(&self, string_to_type: &str) -> Result<&Self, Error> {
-        for c in string_to_type.split("") {
-            // split call above will have empty string at start and end which we won't type
-            if c == "" {
-                continue;
-            }
-            self.press_key(c)?;
-        }
+        self.keyboard().type_str(string_to_type)?;
         Ok(self)
     }
 
+    /// Presses and releases a single key, see `Keyboard::press`
     pub fn press_key(&self, key: &str) -> Result<&Self, Error> {
-        let definition = keys::get_key_definition(key)?;
+        self.keyboard().press(key)?;
+        Ok(self)
+    }
 
-        // See https://github.com/GoogleChrome/puppeteer/blob/62da2366c65b335751896afbb0206f23c61436f1/lib/Input.js#L114-L115
-        let text = definition.text.or_else(|| {
-            if definition.key.len() == 1 {
-                Some(definition.key)
-            } else {
-                None
-            }
-        });
+    /// Access to lower-level keyboard events, such as holding down modifier keys
+    pub fn keyboard(&self) -> Keyboard {
+        Keyboard::new(self)
+    }
 
-        // See https://github.com/GoogleChrome/puppeteer/blob/62da2366c65b335751896afbb0206f23c61436f1/lib/Input.js#L52
-        let key_down_event_type = if text.is_some() {
-            "keyDown"
-        } else {
-            "rawKeyDown"
-        };
+    /// Changes the keyboard layout used for key presses and typing (US by default)
+    pub fn set_keyboard_layout(&self, layout: KeyboardLayout) -> &Self {
+        *self.keyboard_layout.lock().unwrap() = Arc::new(layout);
+        self
+    }
 
-        let key = Some(definition.key);
-        let code = Some(definition.code);
+    pub fn get_keyboard_layout(&self) -> Arc<KeyboardLayout> {
+        Arc::clone(&self.keyboard_layout.lock().unwrap())
+    }
 
-        self.call_method(input::methods::DispatchKeyEvent {
-            event_type: key_down_event_type,
-            key,
-            text,
-            code: Some(definition.code),
-            windows_virtual_key_code: definition.key_code,
-            native_virtual_key_code: definition.key_code,
-        })?;
-        self.call_method(input::methods::DispatchKeyEvent {
-            event_type: "keyUp",
-            key,
-            text,
-            code,
-            windows_virtual_key_code: definition.key_code,
-            native_virtual_key_code: definition.key_code,
-        })?;
-        Ok(self)
+    /// Access to lower-level mouse events, such as pressing other buttons or scrolling
+    pub fn mouse(&self) -> Mouse {
+        Mouse::new(self)
     }
 
     /// Moves the mouse to this point (dispatches a mouseMoved event)
@@ -391,12 +752,7 @@ impl<'a> Tab {
             warn!("Midpoint of element shouldn't be 0,0. Something is probably wrong.")
         }
 
-        self.call_method(input::methods::DispatchMouseEvent {
-            event_type: "mouseMoved",
-            x: point.x,
-            y: point.y,
-            ..Default::default()
-        })?;
+        self.mouse().move_to(point)?;
         Ok(self)
     }
 
@@ -406,21 +762,54 @@ impl<'a> Tab {
             warn!("Midpoint of element shouldn't be 0,0. Something is probably wrong.")
         }
 
-        self.move_mouse_to_point(point)?;
+        self.mouse().click(point, MouseButton::Left, 1)?;
+        Ok(self)
+    }
 
-        self.call_method(input::methods::DispatchMouseEvent {
-            event_type: "mousePressed",
+    /// Taps this point with a finger (a synthesized touch gesture, so `touchstart`, `touchend`
+    /// and then `click` are fired)
+    pub fn tap_point(&self, point: Point) -> Result<&Self, Error> {
+        trace!("Tapping point: {:?}", point);
+        self.call_method(input::methods::SynthesizeTapGesture {
             x: point.x,
             y: point.y,
-            button: Some("left"),
-            click_count: Some(1),
+            gesture_source_type: Some("touch"),
+            ..Default::default()
         })?;
-        self.call_method(input::methods::DispatchMouseEvent {
-            event_type: "mouseReleased",
+        Ok(self)
+    }
+
+    /// Drags a finger across the screen from `from` to `to`, dispatching `touchstart`, a series
+    /// of `touchmove`s and `touchend`, for pages with swipe handlers.
+    pub fn swipe(&self, from: Point, to: Point) -> Result<&Self, Error> {
+        const STEPS: u32 = 10;
+        debug!("Swiping from {:?} to {:?}", from, to);
+        let modifiers = Some(self.keyboard().modifiers());
+        let touch_point = |point: Point| input::methods::TouchPoint {
             x: point.x,
             y: point.y,
-            button: Some("left"),
-            click_count: Some(1),
+            id: Some(0),
+            ..Default::default()
+        };
+
+        self.call_method(input::methods::DispatchTouchEvent {
+            event_type: "touchStart",
+            touch_points: vec![touch_point(from)],
+            modifiers,
+        })?;
+        for step in 1..=STEPS {
+            let fraction = f64::from(step) / f64::from(STEPS);
+            let point = from + (to - from) * fraction;
+            self.call_method(input::methods::DispatchTouchEvent {
+                event_type: "touchMove",
+                touch_points: vec![touch_point(point)],
+                modifiers,
+            })?;
+        }
+        self.call_method(input::methods::DispatchTouchEvent {
+            event_type: "touchEnd",
+            touch_points: vec![],
+            modifiers,
         })?;
         Ok(self)
     }
@@ -456,30 +845,221 @@ impl<'a> Tab {
         clip: Option<page::Viewport>,
         from_surface: bool,
     ) -> Result<Vec<u8>, Error> {
-        let (format, quality) = match format {
-            page::ScreenshotFormat::JPEG(quality) => {
-                (page::InternalScreenshotFormat::JPEG, quality)
-            }
-            page::ScreenshotFormat::PNG => (page::InternalScreenshotFormat::PNG, None),
+        screenshot::capture_raw(self, format, clip, from_surface, false)
+    }
+
+    /// Capture a screenshot of the current page, see `ScreenshotOptions`.
+    ///
+    /// With `full_page` or `device_scale_factor`, the viewport is resized while capturing,
+    /// and then put back the way `set_device_metrics_override` left it.
+    ///
+    /// ```rust,no_run
+    /// # use failure::Error;
+    /// # fn main() -> Result<(), Error> {
+    /// #
+    /// use headless_chrome::{protocol::page::ScreenshotOptionsBuilder, Browser, LaunchOptionsBuilder};
+    /// let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+    /// let tab = browser.wait_for_initial_tab()?;
+    /// tab.navigate_to("https://en.wikipedia.org/wiki/WebKit")?
+    ///     .wait_until_navigated()?;
+    /// let options = ScreenshotOptionsBuilder::default().full_page(true).build().unwrap();
+    /// let png_data = tab.capture_screenshot_with_options(options)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn capture_screenshot_with_options(
+        &self,
+        options: page::ScreenshotOptions,
+    ) -> Result<Vec<u8>, Error> {
+        screenshot::capture(self, &options)
+    }
+
+    /// Emulates a screen of the given size and device scale factor, e.g. a phone's. Screenshots
+    /// that have to resize the viewport put this override back afterwards, which they can't do
+    /// for overrides set with `call_method`.
+    pub fn set_device_metrics_override(
+        &self,
+        metrics: emulation::methods::SetDeviceMetricsOverride,
+    ) -> Result<&Self, Error> {
+        self.call_method(metrics.clone())?;
+        *self.device_metrics_override.lock().unwrap() = Some(metrics);
+        Ok(self)
+    }
+
+    /// Removes the override set with `set_device_metrics_override`, going back to the window's
+    /// own size.
+    pub fn clear_device_metrics_override(&self) -> Result<&Self, Error> {
+        self.call_method(emulation::methods::ClearDeviceMetricsOverride {})?;
+        *self.device_metrics_override.lock().unwrap() = None;
+        Ok(self)
+    }
+
+    /// The override set with `set_device_metrics_override`, if it hasn't been cleared.
+    pub fn get_device_metrics_override(
+        &self,
+    ) -> Option<emulation::methods::SetDeviceMetricsOverride> {
+        self.device_metrics_override.lock().unwrap().clone()
+    }
+
+    /// Starts recording the page, returning a channel that frames arrive on until
+    /// `stop_screencast` is called. Frames are only sent when something on the page changes.
+    ///
+    /// ```rust,no_run
+    /// # use failure::Error;
+    /// # fn main() -> Result<(), Error> {
+    /// #
+    /// use headless_chrome::{Browser, LaunchOptionsBuilder};
+    /// use headless_chrome::browser::tab::screencast::ScreencastOptionsBuilder;
+    /// let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+    /// let tab = browser.wait_for_initial_tab()?;
+    ///
+    /// let frames = tab.start_screencast(ScreencastOptionsBuilder::default().build().unwrap())?;
+    /// tab.navigate_to("https://www.wikipedia.org")?.wait_until_navigated()?;
+    /// tab.stop_screencast()?;
+    /// for frame in frames {
+    ///     println!("{:?}: {} bytes", frame.timestamp(), frame.data.len());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_screencast(
+        &self,
+        options: ScreencastOptions,
+    ) -> Result<mpsc::Receiver<ScreencastFrame>, Error> {
+        let (sender, receiver) = mpsc::channel();
+        *self.screencast.lock().unwrap() = Some((sender, options.format));
+
+        let (format, quality) = match options.format {
+            ScreencastFormat::JPEG(quality) => ("jpeg", quality),
+            ScreencastFormat::PNG => ("png", None),
         };
-        let data = self
-            .call_method(page::methods::CaptureScreenshot {
-                format,
-                clip,
-                quality,
-                from_surface,
-            })?
-            .data;
-        base64::decode(&data).map_err(Into::into)
+        let started = self.call_method(page::methods::StartScreencast {
+            format,
+            quality,
+            max_width: options.max_width,
+            max_height: options.max_height,
+            every_nth_frame: options.every_nth_frame,
+        });
+        if let Err(error) = started {
+            self.screencast.lock().unwrap().take();
+            return Err(error);
+        }
+        Ok(receiver)
+    }
+
+    /// Starts recording the page, writing frames into `dir` as they arrive, see
+    /// `screencast::write_frames_to_dir`. Join the returned thread after calling
+    /// `stop_screencast` to get the paths of the files written. `dir` is created first if it
+    /// doesn't exist.
+    pub fn start_screencast_to_dir<P: AsRef<Path>>(
+        &self,
+        options: ScreencastOptions,
+        dir: P,
+    ) -> Result<thread::JoinHandle<Result<Vec<PathBuf>, std::io::Error>>, Error> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+        let frames = self.start_screencast(options)?;
+        Ok(thread::spawn(move || {
+            screencast::write_frames_to_dir(frames, &dir)
+        }))
+    }
+
+    /// Stops the screencast, which ends the channel of frames once the remaining ones have
+    /// been received.
+    pub fn stop_screencast(&self) -> Result<&Self, Error> {
+        let stopped = self.call_method(page::methods::StopScreencast {});
+        self.screencast.lock().unwrap().take();
+        stopped?;
+        Ok(self)
     }
 
     pub fn print_to_pdf(&self, options: Option<page::PrintToPdfOptions>) -> Result<Vec<u8>, Error> {
         let data = self
-            .call_method(page::methods::PrintToPdf { options })?
+            .call_method(page::methods::PrintToPdf {
+                options,
+                transfer_mode: None,
+            })?
             .data;
         base64::decode(&data).map_err(Into::into)
     }
 
+    /// Like `print_to_pdf`, but has Chrome return the PDF as a stream which is written to
+    /// `writer` a chunk at a time, so that big documents never have to fit in memory (or in a
+    /// single websocket message). `on_progress` is called with the number of bytes written so
+    /// far after each chunk. Returns the size of the PDF in bytes.
+    ///
+    /// ```rust,no_run
+    /// # use failure::Error;
+    /// # fn main() -> Result<(), Error> {
+    /// #
+    /// use std::fs::File;
+    /// use headless_chrome::{Browser, LaunchOptionsBuilder};
+    /// let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+    /// let tab = browser.wait_for_initial_tab()?;
+    /// tab.navigate_to("https://en.wikipedia.org/wiki/WebKit")?.wait_until_navigated()?;
+    ///
+    /// let file = File::create("webkit.pdf")?;
+    /// tab.print_to_pdf_stream(None, file, |written| println!("{} bytes written", written))?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn print_to_pdf_stream<W, F>(
+        &self,
+        options: Option<page::PrintToPdfOptions>,
+        mut writer: W,
+        mut on_progress: F,
+    ) -> Result<u64, Error>
+    where
+        W: std::io::Write,
+        F: FnMut(u64),
+    {
+        let stream = self
+            .call_method(page::methods::PrintToPdf {
+                options,
+                transfer_mode: Some("ReturnAsStream"),
+            })?
+            .stream
+            .ok_or_else(|| format_err!("Chrome didn't return a stream handle for the PDF"))?;
+
+        let mut written = 0;
+        let copied = loop {
+            let chunk = match self.call_method(io::methods::Read {
+                handle: &stream,
+                offset: None,
+                size: Some(PDF_STREAM_CHUNK_SIZE),
+            }) {
+                Ok(chunk) => chunk,
+                Err(error) => break Err(error),
+            };
+            let data = if chunk.base64_encoded {
+                match base64::decode(&chunk.data) {
+                    Ok(data) => data,
+                    Err(error) => break Err(error.into()),
+                }
+            } else {
+                chunk.data.into_bytes()
+            };
+            if let Err(error) = writer.write_all(&data) {
+                break Err(error.into());
+            }
+            written += data.len() as u64;
+            on_progress(written);
+            if chunk.eof {
+                break writer.flush().map_err(Into::into);
+            }
+        };
+
+        // the stream has to be closed even if reading it failed
+        let closed = self.call_method(io::methods::Close { handle: &stream });
+        copied?;
+        closed?;
+        trace!("Streamed {} bytes of PDF", written);
+        Ok(written)
+    }
+
     /// Reloads given page optionally ignoring the cache
     ///
     /// If `ignore_cache` is true, the browser cache is ignored (as if the user pressed Shift+F5).
@@ -497,6 +1077,46 @@ impl<'a> Tab {
         Ok(self)
     }
 
+    /// Closes this tab.
+    ///
+    /// If `run_beforeunload` is true, the page's `beforeunload` handlers are run first (via
+    /// `Page.close`), and those may keep the tab open, so this returns without waiting for it to
+    /// go away. Otherwise the target is closed straight away and this waits until the browser has
+    /// removed it from its list of tabs.
+    pub fn close(&self, run_beforeunload: bool) -> Result<(), Error> {
+        debug!(
+            "Closing tab {} (run_beforeunload: {})",
+            self.target_id, run_beforeunload
+        );
+        if run_beforeunload {
+            self.call_method(page::methods::Close {})?;
+            return Ok(());
+        }
+
+        let success = self
+            .transport
+            .call_method_on_browser(target::methods::CloseTarget {
+                target_id: &self.target_id,
+            })?
+            .success;
+        if !success {
+            return Err(CloseFailed {
+                target_id: self.target_id.clone(),
+            }
+            .into());
+        }
+
+        let closed = Arc::clone(&self.closed);
+        util::Wait::with_timeout(Duration::from_secs(10)).until(|| {
+            if closed.load(Ordering::SeqCst) {
+                Some(())
+            } else {
+                None
+            }
+        })?;
+        Ok(())
+    }
+
     /// Enables the profiler
     pub fn enable_profiler(&self) -> Result<&Self, Error> {
         self.call_method(profiler::methods::Enable {})?;
@@ -554,6 +1174,166 @@ impl<'a> Tab {
         Ok(script_coverages)
     }
 
+    /// Takes the precise coverage (see `take_precise_js_coverage`) and maps it onto the lines,
+    /// functions and branches of each script's source, ready to be written out with
+    /// `coverage::to_lcov` or `coverage::to_istanbul`.
+    ///
+    /// Only scripts whose URL matches `url_pattern` are included, if given, in which '*' stands
+    /// for any number of characters and '?' for exactly one. Scripts without a URL (e.g. ones
+    /// run with `eval`) are always left out, as are scripts whose source can no longer be
+    /// fetched, such as ones from a page that has since been navigated away from.
+    ///
+    /// Enables the debugger, to fetch the scripts' sources.
+    pub fn take_js_coverage_report(
+        &self,
+        url_pattern: Option<&str>,
+    ) -> Result<Vec<coverage::ScriptReport>, Error> {
+        let script_coverages = self.take_precise_js_coverage()?;
+        self.enable_debugger()?;
+
+        let mut reports = vec![];
+        for script_coverage in script_coverages {
+            if script_coverage.url.is_empty() {
+                continue;
+            }
+            if let Some(pattern) = url_pattern {
+                if !coverage::url_matches(pattern, &script_coverage.url) {
+                    continue;
+                }
+            }
+            let source = match self.get_script_source(&script_coverage.script_id) {
+                Ok(source) => source,
+                Err(error) => {
+                    debug!(
+                        "Couldn't get source of script {}: {}",
+                        script_coverage.url, error
+                    );
+                    continue;
+                }
+            };
+            reports.push(coverage::ScriptReport::new(&script_coverage, &source));
+        }
+        Ok(reports)
+    }
+
+    /// Starts tracking which CSS rules are used to style the page.
+    ///
+    /// Equivalent to hitting the record button in the "coverage" tab in Chrome DevTools, like
+    /// `start_js_coverage`, but for stylesheets. Enables the DOM and CSS domains.
+    pub fn start_css_coverage(&self) -> Result<&Self, Error> {
+        // re-enabling makes Chrome report every stylesheet currently on the page again
+        self.style_sheets.lock().unwrap().clear();
+        self.call_method(dom::methods::Enable {})?;
+        self.call_method(css::methods::Disable {})?;
+        self.call_method(css::methods::Enable {})?;
+        self.call_method(css::methods::StartRuleUsageTracking {})?;
+        Ok(self)
+    }
+
+    /// Stops tracking CSS rule usage, and returns which parts of each stylesheet's text were
+    /// used since `start_css_coverage` was called.
+    ///
+    /// Only the page's own stylesheets are included, not those of the browser or of extensions,
+    /// and stylesheets that have since been removed from the page (including by navigating
+    /// away) are left out. They're in the order Chrome reported them being added to the page.
+    pub fn stop_css_coverage(&self) -> Result<Vec<coverage::StyleSheetCoverage>, Error> {
+        let rule_usage = self
+            .call_method(css::methods::StopRuleUsageTracking {})?
+            .rule_usage;
+
+        let mut rules_by_style_sheet: HashMap<&str, Vec<css::RuleUsage>> = HashMap::new();
+        for rule in &rule_usage {
+            rules_by_style_sheet
+                .entry(&rule.style_sheet_id)
+                .or_default()
+                .push(rule.clone());
+        }
+
+        let headers = self.style_sheets.lock().unwrap().clone();
+        let style_sheets = headers.iter().filter(|header| header.origin == "regular");
+
+        let mut coverage = vec![];
+        for header in style_sheets {
+            let text = match self.call_method(css::methods::GetStyleSheetText {
+                style_sheet_id: &header.style_sheet_id,
+            }) {
+                Ok(text) => text.text,
+                Err(error) => {
+                    debug!(
+                        "Couldn't get text of stylesheet {}: {}",
+                        header.style_sheet_id, error
+                    );
+                    continue;
+                }
+            };
+            let rules = rules_by_style_sheet
+                .get(header.style_sheet_id.as_str())
+                .map_or(&[][..], Vec::as_slice);
+            coverage.push(coverage::StyleSheetCoverage::new(
+                header.style_sheet_id.clone(),
+                header.source_url.clone(),
+                header.is_inline,
+                text,
+                rules,
+            ));
+        }
+        Ok(coverage)
+    }
+
+    /// Starts recording a sampling CPU profile of the page's JS, taking a sample every
+    /// `sampling_interval` (Chrome's default is 1ms).
+    ///
+    /// Will return error unless `enable_profiler` has been called.
+    ///
+    /// Equivalent to hitting the record button in the "JavaScript Profiler" tab in Chrome
+    /// DevTools.
+    pub fn start_cpu_profile(&self, sampling_interval: Option<Duration>) -> Result<&Self, Error> {
+        if let Some(sampling_interval) = sampling_interval {
+            self.call_method(profiler::methods::SetSamplingInterval {
+                interval: u32::try_from(sampling_interval.as_micros().max(1)).unwrap_or(u32::MAX),
+            })?;
+        }
+        self.call_method(profiler::methods::Start {})?;
+        Ok(self)
+    }
+
+    /// Stops recording the CPU profile started by `start_cpu_profile`, and returns it.
+    pub fn stop_cpu_profile(&self) -> Result<profiler::Profile, Error> {
+        Ok(self.call_method(profiler::methods::Stop {})?.profile)
+    }
+
+    /// Records a CPU profile of the page while running `action`.
+    ///
+    /// ```rust,no_run
+    /// # use failure::Error;
+    /// # fn main() -> Result<(), Error> {
+    /// #
+    /// # use headless_chrome::{Browser, LaunchOptionsBuilder};
+    /// # let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+    /// let tab = browser.wait_for_initial_tab()?;
+    /// let profile = tab.record_cpu_profile(|| {
+    ///     tab.navigate_to("https://www.wikipedia.org")?.wait_until_navigated()?;
+    ///     Ok(())
+    /// })?;
+    /// std::fs::write("wikipedia.cpuprofile", profile.to_cpuprofile())?;
+    /// std::fs::write("wikipedia.folded", profile.to_collapsed_stacks())?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn record_cpu_profile<F>(&self, action: F) -> Result<profiler::Profile, Error>
+    where
+        F: FnOnce() -> Result<(), Error>,
+    {
+        self.enable_profiler()?;
+        self.start_cpu_profile(None)?;
+        let result = action();
+        // stop profiling even if the action failed
+        let profile = self.stop_cpu_profile();
+        result?;
+        profile
+    }
+
     /// Allows you to inspect outgoing network requests from the tab, and optionally return
     /// your own responses to them
     ///
@@ -0,0 +1,201 @@
+use std::sync::atomic::Ordering;
+
+use failure::Error;
+use log::*;
+
+use super::point::Point;
+use super::Tab;
+use crate::protocol::input;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+}
+
+impl MouseButton {
+    fn as_str(self) -> &'static str {
+        match self {
+            MouseButton::Left => "left",
+            MouseButton::Right => "right",
+            MouseButton::Middle => "middle",
+            MouseButton::Back => "back",
+            MouseButton::Forward => "forward",
+        }
+    }
+
+    /// This button's bit in the `buttons` field of mouse events
+    fn bit(self) -> u32 {
+        match self {
+            MouseButton::Left => 1,
+            MouseButton::Right => 2,
+            MouseButton::Middle => 4,
+            MouseButton::Back => 8,
+            MouseButton::Forward => 16,
+        }
+    }
+}
+
+/// Simulates mouse input on a tab, keeping track of the pointer's position and which buttons
+/// are held down. Modifier keys held via `Tab::keyboard` are applied to mouse events too.
+///
+/// ```rust,no_run
+/// # use failure::Error;
+/// # fn main() -> Result<(), Error> {
+/// #
+/// use headless_chrome::{Browser, LaunchOptionsBuilder};
+/// use headless_chrome::browser::tab::{mouse::MouseButton, point::Point};
+/// let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+/// let tab = browser.wait_for_initial_tab()?;
+///
+/// // draw a line on a canvas
+/// tab.mouse()
+///     .move_to(Point { x: 10.0, y: 10.0 })?
+///     .down(MouseButton::Left, 1)?
+///     .move_in_steps(Point { x: 200.0, y: 100.0 }, 20)?
+///     .up(MouseButton::Left, 1)?;
+///
+/// tab.mouse().wheel(0.0, 500.0)?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct Mouse<'a> {
+    tab: &'a Tab,
+}
+
+impl<'a> Mouse<'a> {
+    pub(crate) fn new(tab: &'a Tab) -> Self {
+        Self { tab }
+    }
+
+    /// Where the pointer was last moved to
+    pub fn position(&self) -> Point {
+        *self.tab.mouse_position.lock().unwrap()
+    }
+
+    /// Moves the pointer straight to `point`, with a single mouseMoved event.
+    pub fn move_to(&self, point: Point) -> Result<&Self, Error> {
+        self.move_in_steps(point, 1)
+    }
+
+    /// Moves the pointer from its current position to `point` in `steps` evenly spaced
+    /// mouseMoved events, for pages that react to the movement itself (e.g. drawing, sliders,
+    /// hover menus).
+    pub fn move_in_steps(&self, point: Point, steps: u32) -> Result<&Self, Error> {
+        let start = self.position();
+        let steps = steps.max(1);
+        for step in 1..=steps {
+            let fraction = f64::from(step) / f64::from(steps);
+            let intermediate = Point {
+                x: start.x + (point.x - start.x) * fraction,
+                y: start.y + (point.y - start.y) * fraction,
+            };
+            self.dispatch("mouseMoved", intermediate, None, None)?;
+            *self.tab.mouse_position.lock().unwrap() = intermediate;
+        }
+        Ok(self)
+    }
+
+    /// Presses `button` at the current position. `click_count` is 1 for a single click, 2 for
+    /// the second press of a double click, etc.
+    pub fn down(&self, button: MouseButton, click_count: u8) -> Result<&Self, Error> {
+        self.tab
+            .mouse_buttons
+            .fetch_or(button.bit(), Ordering::SeqCst);
+        self.dispatch(
+            "mousePressed",
+            self.position(),
+            Some(button),
+            Some(click_count),
+        )?;
+        Ok(self)
+    }
+
+    /// Releases `button` at the current position.
+    pub fn up(&self, button: MouseButton, click_count: u8) -> Result<&Self, Error> {
+        self.tab
+            .mouse_buttons
+            .fetch_and(!button.bit(), Ordering::SeqCst);
+        self.dispatch(
+            "mouseReleased",
+            self.position(),
+            Some(button),
+            Some(click_count),
+        )?;
+        Ok(self)
+    }
+
+    /// Moves to `point` and clicks `button` there `click_count` times, so that e.g. a count of
+    /// 2 fires two click events followed by a dblclick.
+    pub fn click(
+        &self,
+        point: Point,
+        button: MouseButton,
+        click_count: u8,
+    ) -> Result<&Self, Error> {
+        trace!(
+            "Clicking {:?} at {:?} {} time(s)",
+            button,
+            point,
+            click_count
+        );
+        self.move_to(point)?;
+        for count in 1..=click_count {
+            self.down(button, count)?;
+            self.up(button, count)?;
+        }
+        Ok(self)
+    }
+
+    /// Scrolls by the given number of CSS pixels at the pointer's position, like a mouse wheel
+    /// or trackpad. Positive values scroll right / down.
+    pub fn wheel(&self, delta_x: f64, delta_y: f64) -> Result<&Self, Error> {
+        let point = self.position();
+        self.tab.call_method(input::methods::DispatchMouseEvent {
+            event_type: "mouseWheel",
+            x: point.x,
+            y: point.y,
+            modifiers: Some(self.tab.keyboard().modifiers()),
+            delta_x: Some(delta_x),
+            delta_y: Some(delta_y),
+            ..Default::default()
+        })?;
+        Ok(self)
+    }
+
+    /// Presses the left button at `from`, moves to `to` in `steps` steps and releases it there.
+    ///
+    /// This works for pages that implement dragging with mouse events. Native HTML5 drag and
+    /// drop (`draggable="true"`) isn't triggered by synthetic mouse events in headless Chrome.
+    pub fn drag_and_drop(&self, from: Point, to: Point, steps: u32) -> Result<&Self, Error> {
+        debug!("Dragging from {:?} to {:?}", from, to);
+        self.move_to(from)?
+            .down(MouseButton::Left, 1)?
+            .move_in_steps(to, steps)?
+            .up(MouseButton::Left, 1)
+    }
+
+    fn dispatch(
+        &self,
+        event_type: &str,
+        point: Point,
+        button: Option<MouseButton>,
+        click_count: Option<u8>,
+    ) -> Result<(), Error> {
+        self.tab.call_method(input::methods::DispatchMouseEvent {
+            event_type,
+            x: point.x,
+            y: point.y,
+            button: button.map(MouseButton::as_str),
+            click_count,
+            modifiers: Some(self.tab.keyboard().modifiers()),
+            buttons: Some(self.tab.mouse_buttons.load(Ordering::SeqCst)),
+            ..Default::default()
+        })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+
+use crate::protocol::page::events::ScreencastFrameMetadata;
+
+/// The format frames of a screencast are delivered in
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ScreencastFormat {
+    /// Optionally compression quality from range [0..100]
+    JPEG(Option<u8>),
+    PNG,
+}
+
+impl ScreencastFormat {
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            ScreencastFormat::JPEG(_) => "jpg",
+            ScreencastFormat::PNG => "png",
+        }
+    }
+}
+
+/// Options for `Tab::start_screencast`
+#[derive(Debug, Clone, Builder)]
+pub struct ScreencastOptions {
+    /// Defaults to JPEG, which is a lot faster to encode than PNG.
+    #[builder(default = "ScreencastFormat::JPEG(None)")]
+    pub format: ScreencastFormat,
+    /// Scale frames down to at most this many pixels wide.
+    #[builder(default = "None")]
+    pub max_width: Option<u32>,
+    /// Scale frames down to at most this many pixels high.
+    #[builder(default = "None")]
+    pub max_height: Option<u32>,
+    /// Only send every nth frame that Chrome renders.
+    #[builder(default = "None")]
+    pub every_nth_frame: Option<u32>,
+}
+
+/// A frame of a screencast. Chrome only sends frames when something on the page changes.
+#[derive(Debug, Clone)]
+pub struct ScreencastFrame {
+    /// The image, in `format`
+    pub data: Vec<u8>,
+    pub format: ScreencastFormat,
+    pub metadata: ScreencastFrameMetadata,
+}
+
+impl ScreencastFrame {
+    /// When the frame was rendered, in seconds since the epoch
+    pub fn timestamp(&self) -> Option<f64> {
+        self.metadata.timestamp
+    }
+}
+
+/// Writes frames to `dir` as they arrive, until the screencast is stopped, returning the paths of
+/// the files written. Files are named after the frame's number and its timestamp in
+/// milliseconds, e.g. `frame-00042-1571234567890.jpg`, so that they sort in the order they
+/// were received. `dir` has to exist already.
+pub fn write_frames_to_dir(
+    frames: Receiver<ScreencastFrame>,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, io::Error> {
+    let mut paths = vec![];
+    for (index, frame) in frames.into_iter().enumerate() {
+        // timestamps are positive and nowhere near u64::MAX milliseconds, and `as` saturates
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let timestamp = frame
+            .timestamp()
+            .map_or(0, |seconds| (seconds * 1000.0).round() as u64);
+        let path = dir.join(format!(
+            "frame-{:05}-{}.{}",
+            index,
+            timestamp,
+            frame.format.file_extension()
+        ));
+        fs::write(&path, &frame.data)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Turning screencasts into animated images, without any external tools. Requires the
+/// `animation` feature.
+#[cfg(feature = "animation")]
+pub mod animation {
+    use std::convert::TryFrom;
+    use std::io::Write;
+
+    use failure::{format_err, Error};
+
+    use super::{ScreencastFormat, ScreencastFrame};
+    use crate::browser::tab::screenshot::{decode_png, encode_png};
+
+    /// How long the last frame is shown, as there's no next frame to measure it by
+    const LAST_FRAME_DELAY_MS: u32 = 1000;
+
+    /// Encodes frames as an endlessly looping GIF, showing each one until the next one's
+    /// timestamp. GIFs only have 256 colors, so expect some banding.
+    pub fn encode_gif<W: Write>(frames: &[ScreencastFrame], writer: W) -> Result<(), Error> {
+        use gif::SetParameter;
+
+        let decoded = decode_frames(frames)?;
+        let (width, height) = match decoded.first() {
+            Some((width, height, _)) => (*width, *height),
+            None => return Err(format_err!("Can't make an animation without any frames")),
+        };
+        let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => return Err(format_err!("Frames are too big for a GIF")),
+        };
+
+        let mut encoder = gif::Encoder::new(writer, width, height, &[])?;
+        encoder.set(gif::Repeat::Infinite)?;
+        for ((_, _, mut pixels), delay) in decoded.into_iter().zip(frame_delays(frames)) {
+            let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+            frame.delay = u16::try_from(delay / 10).unwrap_or(u16::MAX);
+            encoder.write_frame(&frame)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes frames as an endlessly looping animated PNG, showing each one until the next
+    /// one's timestamp. APNGs are lossless, but a lot bigger than GIFs.
+    pub fn encode_apng<W: Write>(frames: &[ScreencastFrame], mut writer: W) -> Result<(), Error> {
+        let decoded = decode_frames(frames)?;
+        if decoded.is_empty() {
+            return Err(format_err!("Can't make an animation without any frames"));
+        }
+
+        writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+        let mut sequence_number: u32 = 0;
+        for (index, ((width, height, pixels), delay)) in
+            decoded.iter().zip(frame_delays(frames)).enumerate()
+        {
+            let png = encode_png(*width, *height, pixels)?;
+            let chunks = png_chunks(&png)?;
+
+            if index == 0 {
+                for (kind, data) in &chunks {
+                    if kind == b"IHDR" {
+                        write_chunk(&mut writer, *b"IHDR", data)?;
+                    }
+                }
+                let mut animation_control = vec![];
+                animation_control.extend_from_slice(&u32::try_from(decoded.len())?.to_be_bytes());
+                // loop forever
+                animation_control.extend_from_slice(&0_u32.to_be_bytes());
+                write_chunk(&mut writer, *b"acTL", &animation_control)?;
+            }
+
+            let mut frame_control = vec![];
+            frame_control.extend_from_slice(&sequence_number.to_be_bytes());
+            frame_control.extend_from_slice(&width.to_be_bytes());
+            frame_control.extend_from_slice(&height.to_be_bytes());
+            // x and y offset
+            frame_control.extend_from_slice(&[0; 8]);
+            // delay in milliseconds
+            frame_control
+                .extend_from_slice(&u16::try_from(delay).unwrap_or(u16::MAX).to_be_bytes());
+            frame_control.extend_from_slice(&1000_u16.to_be_bytes());
+            // don't dispose of or blend with the previous frame
+            frame_control.extend_from_slice(&[0, 0]);
+            write_chunk(&mut writer, *b"fcTL", &frame_control)?;
+            sequence_number += 1;
+
+            for (kind, data) in chunks.into_iter().filter(|(kind, _)| kind == b"IDAT") {
+                if index == 0 {
+                    write_chunk(&mut writer, kind, &data)?;
+                } else {
+                    let mut frame_data = sequence_number.to_be_bytes().to_vec();
+                    frame_data.extend_from_slice(&data);
+                    write_chunk(&mut writer, *b"fdAT", &frame_data)?;
+                    sequence_number += 1;
+                }
+            }
+        }
+        write_chunk(&mut writer, *b"IEND", &[])?;
+        Ok(())
+    }
+
+    /// How long each frame should be shown for, in milliseconds
+    // the delays are rounded and not negative, and `as` saturates any that are too long
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn frame_delays(frames: &[ScreencastFrame]) -> Vec<u32> {
+        let mut delays: Vec<u32> = frames
+            .windows(2)
+            .map(|pair| match (pair[0].timestamp(), pair[1].timestamp()) {
+                (Some(this), Some(next)) => ((next - this).max(0.0) * 1000.0).round() as u32,
+                _ => LAST_FRAME_DELAY_MS,
+            })
+            .collect();
+        if !frames.is_empty() {
+            delays.push(LAST_FRAME_DELAY_MS);
+        }
+        delays
+    }
+
+    /// Decodes frames into RGBA pixels, making sure they're all the same size.
+    fn decode_frames(frames: &[ScreencastFrame]) -> Result<Vec<(u32, u32, Vec<u8>)>, Error> {
+        let decoded = frames
+            .iter()
+            .map(decode_frame)
+            .collect::<Result<Vec<_>, Error>>()?;
+        if let Some((width, height, _)) = decoded.first() {
+            if decoded.iter().any(|(w, h, _)| (w, h) != (width, height)) {
+                return Err(format_err!(
+                    "Frames have different sizes, was the viewport resized?"
+                ));
+            }
+        }
+        Ok(decoded)
+    }
+
+    fn decode_frame(frame: &ScreencastFrame) -> Result<(u32, u32, Vec<u8>), Error> {
+        match frame.format {
+            ScreencastFormat::PNG => decode_png(&frame.data),
+            ScreencastFormat::JPEG(_) => {
+                let mut decoder = jpeg_decoder::Decoder::new(&frame.data[..]);
+                let pixels = decoder.decode()?;
+                let info = decoder
+                    .info()
+                    .ok_or_else(|| format_err!("JPEG frame has no image info"))?;
+                let pixels = match info.pixel_format {
+                    jpeg_decoder::PixelFormat::RGB24 => pixels
+                        .chunks(3)
+                        .flat_map(|rgb| vec![rgb[0], rgb[1], rgb[2], 255])
+                        .collect(),
+                    jpeg_decoder::PixelFormat::L8 => {
+                        pixels.iter().flat_map(|&l| vec![l, l, l, 255]).collect()
+                    }
+                    jpeg_decoder::PixelFormat::CMYK32 => {
+                        return Err(format_err!("CMYK JPEG frames aren't supported"));
+                    }
+                };
+                Ok((u32::from(info.width), u32::from(info.height), pixels))
+            }
+        }
+    }
+
+    /// Splits a PNG into its chunks' types and data
+    fn png_chunks(png: &[u8]) -> Result<Vec<([u8; 4], Vec<u8>)>, Error> {
+        let mut chunks = vec![];
+        let mut rest = &png[8..];
+        while rest.len() >= 12 {
+            let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            if rest.len() < 12 + length {
+                return Err(format_err!("Truncated PNG chunk"));
+            }
+            let kind = [rest[4], rest[5], rest[6], rest[7]];
+            chunks.push((kind, rest[8..8 + length].to_vec()));
+            rest = &rest[12 + length..];
+        }
+        Ok(chunks)
+    }
+
+    fn write_chunk<W: Write>(writer: &mut W, kind: [u8; 4], data: &[u8]) -> Result<(), Error> {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&kind);
+        hasher.update(data);
+        writer.write_all(&u32::try_from(data.len())?.to_be_bytes())?;
+        writer.write_all(&kind)?;
+        writer.write_all(data)?;
+        writer.write_all(&hasher.finalize().to_be_bytes())?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::protocol::page::events::ScreencastFrameMetadata;
+
+        fn frame(color: [u8; 4], timestamp: f64) -> ScreencastFrame {
+            let pixels: Vec<u8> = color.iter().cycle().take(4 * 4 * 4).cloned().collect();
+            ScreencastFrame {
+                data: encode_png(4, 4, &pixels).unwrap(),
+                format: ScreencastFormat::PNG,
+                metadata: ScreencastFrameMetadata {
+                    offset_top: 0.0,
+                    page_scale_factor: 1.0,
+                    device_width: 4.0,
+                    device_height: 4.0,
+                    scroll_offset_x: 0.0,
+                    scroll_offset_y: 0.0,
+                    timestamp: Some(timestamp),
+                },
+            }
+        }
+
+        #[test]
+        fn delays_between_frames() {
+            let frames = vec![
+                frame([0; 4], 10.0),
+                frame([0; 4], 10.25),
+                frame([0; 4], 11.0),
+            ];
+            assert_eq!(vec![250, 750, LAST_FRAME_DELAY_MS], frame_delays(&frames));
+            assert!(frame_delays(&[]).is_empty());
+        }
+
+        #[test]
+        fn encodes_apng() {
+            let frames = vec![frame([255, 0, 0, 255], 1.0), frame([0, 0, 255, 255], 1.5)];
+            let mut apng = vec![];
+            encode_apng(&frames, &mut apng).unwrap();
+
+            let kinds: Vec<_> = png_chunks(&apng)
+                .unwrap()
+                .into_iter()
+                .map(|(kind, _)| kind)
+                .collect();
+            assert_eq!(
+                vec![b"IHDR", b"acTL", b"fcTL", b"IDAT", b"fcTL", b"fdAT", b"IEND"],
+                kinds.iter().collect::<Vec<_>>()
+            );
+            // programs that don't know about APNG just show the first frame
+            let (_, _, pixels) = decode_png(&apng).unwrap();
+            assert_eq!(&[255, 0, 0, 255], &pixels[0..4]);
+        }
+
+        #[test]
+        fn encodes_gif() {
+            let frames = vec![frame([255, 0, 0, 255], 1.0), frame([0, 0, 255, 255], 1.5)];
+            let mut data = vec![];
+            encode_gif(&frames, &mut data).unwrap();
+            assert!(data.starts_with(b"GIF89a"));
+
+            let mut decoder = gif::Decoder::new(&data[..]).read_info().unwrap();
+            let first = decoder.read_next_frame().unwrap().unwrap();
+            assert_eq!(50, first.delay);
+            assert!(decoder.read_next_frame().unwrap().is_some());
+            assert!(decoder.read_next_frame().unwrap().is_none());
+        }
+
+        #[test]
+        fn refuses_frames_of_different_sizes() {
+            let mut small = frame([0; 4], 2.0);
+            small.data = encode_png(2, 2, &[0; 16]).unwrap();
+            assert!(encode_gif(&[frame([0; 4], 1.0), small], vec![]).is_err());
+        }
+    }
+}
@@ -0,0 +1,357 @@
+use failure::{format_err, Error, Fail};
+use log::*;
+
+use super::Tab;
+use crate::protocol::page::{self, ScreenshotFormat, ScreenshotOptions};
+use crate::protocol::{emulation, runtime};
+
+const TRANSPARENT: emulation::RGBA = emulation::RGBA {
+    r: 0,
+    g: 0,
+    b: 0,
+    a: 0.0,
+};
+
+/// The largest image, in device pixels along either side, that Chrome can render in one go
+const MAX_TEXTURE_SIZE: u32 = 16384;
+
+#[derive(Debug, Fail)]
+#[fail(
+    display = "The page is {} pixels tall, so its screenshot has to be stitched together from \
+               several captures, which is only supported for PNG",
+    height
+)]
+pub struct PageTooTall {
+    pub height: u32,
+}
+
+/// Captures a screenshot, applying and afterwards removing whatever overrides `options` need.
+pub(crate) fn capture(tab: &Tab, options: &ScreenshotOptions) -> Result<Vec<u8>, Error> {
+    if options.omit_background {
+        set_background(tab, Some(TRANSPARENT))?;
+    }
+
+    let screenshot = if options.full_page && options.clip.is_none() {
+        capture_full_page(tab, options)
+    } else if let Some(device_scale_factor) = options.device_scale_factor {
+        capture_scaled(tab, options, device_scale_factor)
+    } else {
+        capture_once(tab, options, options.clip.clone())
+    };
+
+    // report the capture error rather than the reset's, if both failed
+    let reset = if options.omit_background {
+        set_background(tab, None)
+    } else {
+        Ok(())
+    };
+    let screenshot = screenshot?;
+    reset?;
+    Ok(screenshot)
+}
+
+/// Captures the viewport (or `clip`) at a different device scale factor, without changing the
+/// size of the viewport in CSS pixels. The override set with `Tab::set_device_metrics_override`,
+/// if any, is put back afterwards.
+fn capture_scaled(
+    tab: &Tab,
+    options: &ScreenshotOptions,
+    device_scale_factor: f64,
+) -> Result<Vec<u8>, Error> {
+    let previous_override = tab.get_device_metrics_override();
+    let viewport = tab
+        .call_method(page::methods::GetLayoutMetrics {})?
+        .layout_viewport;
+    let screenshot = set_device_metrics(
+        tab,
+        whole_pixels(viewport.client_width.round()),
+        whole_pixels(viewport.client_height.round()),
+        device_scale_factor,
+    )
+    .and_then(|()| capture_once(tab, options, options.clip.clone()));
+
+    let restored = restore_device_metrics(tab, previous_override);
+    let screenshot = screenshot?;
+    restored?;
+    Ok(screenshot)
+}
+
+/// Calls Page.captureScreenshot as is.
+pub(crate) fn capture_raw(
+    tab: &Tab,
+    format: ScreenshotFormat,
+    clip: Option<page::Viewport>,
+    from_surface: bool,
+    capture_beyond_viewport: bool,
+) -> Result<Vec<u8>, Error> {
+    let (format, quality) = match format {
+        ScreenshotFormat::JPEG(quality) => (page::InternalScreenshotFormat::JPEG, quality),
+        ScreenshotFormat::PNG => (page::InternalScreenshotFormat::PNG, None),
+        ScreenshotFormat::WEBP(quality) => (page::InternalScreenshotFormat::WEBP, quality),
+    };
+    let data = tab
+        .call_method(page::methods::CaptureScreenshot {
+            format,
+            clip,
+            quality,
+            from_surface,
+            capture_beyond_viewport: if capture_beyond_viewport {
+                Some(true)
+            } else {
+                None
+            },
+        })?
+        .data;
+    base64::decode(&data).map_err(Into::into)
+}
+
+fn capture_once(
+    tab: &Tab,
+    options: &ScreenshotOptions,
+    clip: Option<page::Viewport>,
+) -> Result<Vec<u8>, Error> {
+    capture_raw(
+        tab,
+        options.format.clone(),
+        clip,
+        options.from_surface,
+        options.capture_beyond_viewport,
+    )
+}
+
+/// Captures the whole scrollable page by temporarily resizing the viewport to fit it. The
+/// override set with `Tab::set_device_metrics_override`, if any, is put back afterwards.
+///
+/// Pages taller than Chrome's texture limit are captured in tiles of the largest possible
+/// height, scrolling down between each one, and stitched together. Elements with a fixed
+/// position will show up once per tile.
+fn capture_full_page(tab: &Tab, options: &ScreenshotOptions) -> Result<Vec<u8>, Error> {
+    let previous_override = tab.get_device_metrics_override();
+    let metrics = tab.call_method(page::methods::GetLayoutMetrics {})?;
+    let content_size = metrics
+        .css_content_size
+        .as_ref()
+        .unwrap_or(&metrics.content_size);
+    let device_pixel_ratio = match options.device_scale_factor {
+        Some(device_scale_factor) => device_scale_factor,
+        None => evaluate_number(tab, "window.devicePixelRatio")?,
+    };
+
+    let max_size = whole_pixels((f64::from(MAX_TEXTURE_SIZE) / device_pixel_ratio).floor());
+    let width = whole_pixels(content_size.width.ceil()).min(max_size);
+    let height = whole_pixels(content_size.height.ceil());
+    debug!(
+        "Capturing full page of {}x{} CSS pixels at device pixel ratio {}",
+        width, height, device_pixel_ratio
+    );
+
+    let screenshot = if height <= max_size {
+        set_device_metrics(tab, width, height, device_pixel_ratio)
+            .and_then(|()| capture_once(tab, options, None))
+    } else {
+        capture_in_tiles(tab, options, width, height, max_size, device_pixel_ratio)
+    };
+
+    // put the viewport back the way it was, even if capturing failed
+    let restored = restore_device_metrics(tab, previous_override).and_then(|()| {
+        scroll_to(
+            tab,
+            metrics.layout_viewport.page_x,
+            metrics.layout_viewport.page_y,
+        )
+    });
+    let screenshot = screenshot?;
+    restored?;
+    Ok(screenshot)
+}
+
+fn capture_in_tiles(
+    tab: &Tab,
+    options: &ScreenshotOptions,
+    width: u32,
+    height: u32,
+    tile_height: u32,
+    device_pixel_ratio: f64,
+) -> Result<Vec<u8>, Error> {
+    match options.format {
+        ScreenshotFormat::PNG => {}
+        _ => return Err(PageTooTall { height }.into()),
+    }
+
+    set_device_metrics(tab, width, tile_height, device_pixel_ratio)?;
+    let mut tiles = vec![];
+    let mut offset = 0;
+    while offset < height {
+        // rather than sticking out past the end of the page, the last tile overlaps the one
+        // before it
+        let scroll_y = offset.min(height - tile_height);
+        scroll_to(tab, 0.0, f64::from(scroll_y))?;
+        let tile = capture_raw(
+            tab,
+            ScreenshotFormat::PNG,
+            None,
+            options.from_surface,
+            false,
+        )?;
+        let overlap = whole_pixels((f64::from(offset - scroll_y) * device_pixel_ratio).round());
+        trace!(
+            "Captured tile at {} (overlap: {} pixels)",
+            scroll_y,
+            overlap
+        );
+        tiles.push((tile, overlap));
+        offset += tile_height;
+    }
+    stitch_vertically(&tiles)
+}
+
+fn set_device_metrics(
+    tab: &Tab,
+    width: u32,
+    height: u32,
+    device_scale_factor: f64,
+) -> Result<(), Error> {
+    tab.call_method(emulation::methods::SetDeviceMetricsOverride {
+        width,
+        height,
+        device_scale_factor,
+        mobile: false,
+    })?;
+    Ok(())
+}
+
+/// Puts back the device metrics override that was in place before the screenshot, or clears
+/// the one the screenshot set if there wasn't any.
+fn restore_device_metrics(
+    tab: &Tab,
+    previous: Option<emulation::methods::SetDeviceMetricsOverride>,
+) -> Result<(), Error> {
+    match previous {
+        Some(previous) => tab.call_method(previous).map(|_| ()),
+        None => tab
+            .call_method(emulation::methods::ClearDeviceMetricsOverride {})
+            .map(|_| ()),
+    }
+}
+
+/// Converts a length that's already been rounded to whole pixels, clamping it to `u32`'s range.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn whole_pixels(length: f64) -> u32 {
+    length.max(0.0).min(f64::from(u32::MAX)) as u32
+}
+
+fn set_background(tab: &Tab, color: Option<emulation::RGBA>) -> Result<(), Error> {
+    tab.call_method(emulation::methods::SetDefaultBackgroundColorOverride { color })?;
+    Ok(())
+}
+
+fn scroll_to(tab: &Tab, x: f64, y: f64) -> Result<(), Error> {
+    tab.call_method(runtime::methods::Evaluate {
+        expression: &format!("window.scrollTo({}, {})", x, y),
+        ..Default::default()
+    })?;
+    Ok(())
+}
+
+fn evaluate_number(tab: &Tab, expression: &str) -> Result<f64, Error> {
+    tab.call_method(runtime::methods::Evaluate {
+        expression,
+        return_by_value: true,
+        await_promise: false,
+    })?
+    .result
+    .value
+    .and_then(|value| value.as_f64())
+    .ok_or_else(|| format_err!("{} isn't a number", expression))
+}
+
+/// Decodes a PNG into 8-bit RGBA pixels, returning its width, height and pixels.
+pub(crate) fn decode_png(data: &[u8]) -> Result<(u32, u32, Vec<u8>), Error> {
+    use png::HasParameters;
+
+    let mut decoder = png::Decoder::new(data);
+    decoder.set(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let (info, mut reader) = decoder.read_info()?;
+    let mut pixels = vec![0; info.buffer_size()];
+    reader.next_frame(&mut pixels)?;
+
+    let pixels = match info.color_type {
+        png::ColorType::RGBA => pixels,
+        png::ColorType::RGB => pixels
+            .chunks(3)
+            .flat_map(|rgb| vec![rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks(2)
+            .flat_map(|ga| vec![ga[0], ga[0], ga[0], ga[1]])
+            .collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&g| vec![g, g, g, 255]).collect(),
+        png::ColorType::Indexed => {
+            return Err(format_err!("Indexed PNGs should have been expanded to RGB"));
+        }
+    };
+    Ok((info.width, info.height, pixels))
+}
+
+/// Encodes 8-bit RGBA pixels as a PNG.
+pub(crate) fn encode_png(width: u32, height: u32, pixels: &[u8]) -> Result<Vec<u8>, Error> {
+    use png::HasParameters;
+
+    let mut data = vec![];
+    {
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set(png::ColorType::RGBA).set(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(pixels)?;
+    }
+    Ok(data)
+}
+
+/// Puts PNG images on top of each other, skipping the given number of rows at the top of each
+/// one.
+fn stitch_vertically(tiles: &[(Vec<u8>, u32)]) -> Result<Vec<u8>, Error> {
+    let mut width = None;
+    let mut height = 0;
+    let mut pixels = vec![];
+    for (tile, skipped_rows) in tiles {
+        let (tile_width, tile_height, tile_pixels) = decode_png(tile)?;
+        if *width.get_or_insert(tile_width) != tile_width {
+            return Err(format_err!("Screenshot tiles have different widths"));
+        }
+        let skipped_rows = (*skipped_rows).min(tile_height);
+        pixels.extend_from_slice(&tile_pixels[(skipped_rows * tile_width * 4) as usize..]);
+        height += tile_height - skipped_rows;
+    }
+    encode_png(width.unwrap_or(0), height, &pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_png(width: u32, height: u32, rgba: [u8; 4]) -> Vec<u8> {
+        let pixels: Vec<u8> = rgba
+            .iter()
+            .cycle()
+            .take((width * height * 4) as usize)
+            .cloned()
+            .collect();
+        encode_png(width, height, &pixels).unwrap()
+    }
+
+    #[test]
+    fn stitches_tiles_dropping_overlaps() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let tiles = vec![(solid_png(3, 4, red), 0), (solid_png(3, 4, blue), 1)];
+
+        let (width, height, pixels) = decode_png(&stitch_vertically(&tiles).unwrap()).unwrap();
+        assert_eq!((3, 7), (width, height));
+        assert_eq!(&red, &pixels[..4]);
+        assert_eq!(&red, &pixels[(3 * 4 * 4 - 4)..(3 * 4 * 4)]);
+        assert_eq!(&blue, &pixels[(3 * 4 * 4)..(3 * 4 * 4 + 4)]);
+        assert_eq!(&blue, &pixels[pixels.len() - 4..]);
+
+        let narrower = vec![(solid_png(3, 4, red), 0), (solid_png(2, 4, blue), 0)];
+        assert!(stitch_vertically(&narrower).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use std::ops::Deref;
+use std::sync::{Arc, Condvar, Mutex};
+
+use failure::{Error, Fail};
+use log::*;
+
+use crate::browser::context::Context;
+use crate::browser::tab::Tab;
+use crate::protocol::network;
+use crate::protocol::storage;
+use crate::protocol::target::methods::CreateTarget;
+
+#[derive(Debug, Fail)]
+#[fail(display = "Every tab in the pool crashed and couldn't be replaced")]
+pub struct NoTabsLeft {}
+
+struct PoolEntry {
+    tab: Arc<Tab>,
+    context_id: Option<String>,
+}
+
+struct PoolState {
+    idle: Vec<PoolEntry>,
+    /// Number of tabs belonging to the pool, whether idle or handed out.
+    size: usize,
+}
+
+/// A fixed number of ready-made tabs, which can be borrowed with `get` and are reset and put
+/// back in the pool when the returned `PooledTab` is dropped.
+///
+/// Tabs are reset by navigating them to `about:blank` and clearing the cookies and storage of
+/// the last page they visited. Tabs with their own browser context also have every other cookie
+/// in that context cleared. Unless the pool was created with separate contexts, its tabs share
+/// a single browser context, so they can still see each other's cookies while borrowed.
+///
+/// Tabs that crash or get closed are replaced with new ones.
+///
+/// ```rust,no_run
+/// # use failure::Error;
+/// # fn main() -> Result<(), Error> {
+/// #
+/// use headless_chrome::{Browser, LaunchOptionsBuilder};
+/// let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+/// let pool = browser.new_tab_pool(4, true)?;
+///
+/// let tab = pool.get()?;
+/// tab.navigate_to("https://www.wikipedia.org")?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct TabPool<'a> {
+    browser: &'a super::Browser,
+    state: Mutex<PoolState>,
+    tab_returned: Condvar,
+    context_ids: Vec<String>,
+}
+
+impl<'a> TabPool<'a> {
+    /// Creates `size` tabs up front. If `separate_contexts` is true, each one gets its own
+    /// browser context (the equivalent of an incognito window), so they don't share cookies or
+    /// a cache.
+    pub fn new(
+        browser: &'a super::Browser,
+        size: usize,
+        separate_contexts: bool,
+    ) -> Result<Self, Error> {
+        let mut idle = Vec::with_capacity(size);
+        let mut context_ids = vec![];
+
+        for _ in 0..size {
+            let context_id = if separate_contexts {
+                let context_id = browser.new_context()?.get_id().to_string();
+                context_ids.push(context_id.clone());
+                Some(context_id)
+            } else {
+                None
+            };
+            let tab = new_tab_in_context(browser, context_id.as_ref())?;
+            idle.push(PoolEntry { tab, context_id });
+        }
+        debug!("Created pool of {} tabs", size);
+
+        Ok(Self {
+            browser,
+            state: Mutex::new(PoolState { idle, size }),
+            tab_returned: Condvar::new(),
+            context_ids,
+        })
+    }
+
+    /// Borrows a tab from the pool, waiting for one to be returned if they're all in use.
+    pub fn get(&self) -> Result<PooledTab<'_, 'a>, Error> {
+        let entry = {
+            let mut state = self.state.lock().unwrap();
+            loop {
+                if let Some(entry) = state.idle.pop() {
+                    break entry;
+                }
+                if state.size == 0 {
+                    return Err(NoTabsLeft {}.into());
+                }
+                state = self.tab_returned.wait(state).unwrap();
+            }
+        };
+
+        // idle tabs can crash too
+        let entry = if entry.tab.is_crashed() || entry.tab.is_closed() {
+            match self.replace(entry) {
+                Some(entry) => entry,
+                None => return self.get(),
+            }
+        } else {
+            entry
+        };
+
+        Ok(PooledTab {
+            pool: self,
+            entry: Some(entry),
+        })
+    }
+
+    /// Borrows a tab from the pool if one is available right now.
+    pub fn try_get(&self) -> Option<PooledTab<'_, 'a>> {
+        let entry = self.state.lock().unwrap().idle.pop()?;
+        let entry = if entry.tab.is_crashed() || entry.tab.is_closed() {
+            self.replace(entry)?
+        } else {
+            entry
+        };
+        Some(PooledTab {
+            pool: self,
+            entry: Some(entry),
+        })
+    }
+
+    /// The number of tabs in the pool, including any that are currently borrowed.
+    pub fn size(&self) -> usize {
+        self.state.lock().unwrap().size
+    }
+
+    fn give_back(&self, entry: PoolEntry) {
+        let entry = if entry.tab.is_crashed() || entry.tab.is_closed() {
+            self.replace(entry)
+        } else if let Err(error) = reset(&entry.tab, entry.context_id.is_some()) {
+            warn!("Couldn't reset pooled tab, replacing it: {}", error);
+            self.replace(entry)
+        } else {
+            Some(entry)
+        };
+
+        if let Some(entry) = entry {
+            self.state.lock().unwrap().idle.push(entry);
+            self.tab_returned.notify_one();
+        } else {
+            // wake everyone up, in case that was the last tab
+            self.tab_returned.notify_all();
+        }
+    }
+
+    /// Closes a broken tab and opens a new one in its place. If that fails, the pool shrinks by
+    /// one.
+    fn replace(&self, broken: PoolEntry) -> Option<PoolEntry> {
+        debug!("Replacing pooled tab {}", broken.tab.get_target_id());
+        close_broken(&broken.tab);
+        match new_tab_in_context(self.browser, broken.context_id.as_ref()) {
+            Ok(tab) => Some(PoolEntry {
+                tab,
+                context_id: broken.context_id,
+            }),
+            Err(error) => {
+                warn!("Couldn't replace pooled tab: {}", error);
+                self.state.lock().unwrap().size -= 1;
+                None
+            }
+        }
+    }
+}
+
+impl<'a> Drop for TabPool<'a> {
+    fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap();
+        for entry in state.idle.drain(..) {
+            if entry.context_id.is_none() {
+                let _ = entry.tab.close(false);
+            }
+        }
+        for context_id in self.context_ids.drain(..) {
+            if let Err(error) = Context::new(self.browser, context_id).dispose() {
+                warn!("Couldn't dispose of pool's browser context: {}", error);
+            }
+        }
+    }
+}
+
+/// A tab borrowed from a `TabPool`, which is reset and returned to the pool when dropped.
+pub struct PooledTab<'p, 'a> {
+    pool: &'p TabPool<'a>,
+    entry: Option<PoolEntry>,
+}
+
+impl<'p, 'a> Deref for PooledTab<'p, 'a> {
+    type Target = Tab;
+
+    fn deref(&self) -> &Tab {
+        &self.entry.as_ref().unwrap().tab
+    }
+}
+
+impl<'p, 'a> Drop for PooledTab<'p, 'a> {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            self.pool.give_back(entry);
+        }
+    }
+}
+
+fn new_tab_in_context(
+    browser: &super::Browser,
+    context_id: Option<&String>,
+) -> Result<Arc<Tab>, Error> {
+    browser.new_tab_with_options(CreateTarget {
+        url: "about:blank",
+        width: None,
+        height: None,
+        browser_context_id: context_id.map(String::as_str),
+        enable_begin_frame_control: None,
+    })
+}
+
+/// Closes the tab unless it's closed already, so that crashed pages don't linger in the browser.
+fn close_broken(tab: &Tab) {
+    if tab.is_closed() {
+        return;
+    }
+    if let Err(error) = tab.close(false) {
+        warn!(
+            "Couldn't close broken pooled tab {}: {}",
+            tab.get_target_id(),
+            error
+        );
+    }
+}
+
+/// Clearing cookies is scoped to the whole browser context, so unless the tab has a context of
+/// its own only the last origin's cookies (included in "all") are cleared, to leave the other
+/// borrowed tabs alone.
+fn reset(tab: &Tab, own_context: bool) -> Result<(), Error> {
+    let last_url = tab.get_url();
+    tab.navigate_to("about:blank")?.wait_until_navigated()?;
+
+    if let Some(origin) = origin_of(&last_url) {
+        tab.call_method(storage::methods::ClearDataForOrigin {
+            origin,
+            storage_types: "all",
+        })?;
+    }
+    if own_context {
+        tab.call_method(network::methods::ClearBrowserCookies {})?;
+    }
+    Ok(())
+}
+
+/// The scheme, host and port of an http(s) URL, which is what Storage.clearDataForOrigin wants.
+fn origin_of(url: &str) -> Option<&str> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return None;
+    }
+    let after_scheme = url.find("://")? + 3;
+    let end = url[after_scheme..]
+        .find(|c| c == '/' || c == '?' || c == '#')
+        .map_or(url.len(), |i| after_scheme + i);
+    Some(&url[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origin_of_urls() {
+        assert_eq!(
+            Some("https://example.com"),
+            origin_of("https://example.com/path?query#fragment")
+        );
+        assert_eq!(
+            Some("http://127.0.0.1:8000"),
+            origin_of("http://127.0.0.1:8000")
+        );
+        assert_eq!(
+            Some("http://localhost:8000"),
+            origin_of("http://localhost:8000?a=b")
+        );
+        assert_eq!(None, origin_of("about:blank"));
+        assert_eq!(None, origin_of("data:text/html,<p>hi</p>"));
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::process::ExitStatus;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc;
 use std::sync::mpsc::Sender;
@@ -14,6 +15,7 @@ use serde;
 use waiting_call_registry::WaitingCallRegistry;
 use web_socket_connection::WebSocketConnection;
 
+use super::process::ProcessHandle;
 use crate::protocol::target;
 use crate::protocol::CallId;
 use crate::protocol::Event;
@@ -59,14 +61,32 @@ pub struct Transport {
     open: Arc<AtomicBool>,
     call_id_counter: Arc<AtomicUsize>,
     loop_shutdown_tx: Mutex<mpsc::Sender<()>>,
+    shutting_down: Arc<AtomicBool>,
+    crash: Arc<Mutex<Option<BrowserCrashed>>>,
 }
 
-#[derive(Debug, Fail)]
+#[derive(Debug, Fail, Clone)]
 #[fail(display = "Unable to make method calls because underlying connection is closed")]
 pub struct ConnectionClosed {}
 
+/// The connection to Chrome went away without us asking it to, e.g. because the process crashed
+/// or was killed.
+///
+/// `exit_status` is only known for browsers we launched ourselves, and only if the process had
+/// actually exited shortly after the connection dropped.
+#[derive(Debug, Fail, Clone)]
+#[fail(
+    display = "Lost connection to Chrome #{:?}, which exited with status {:?}",
+    process_id, exit_status
+)]
+pub struct BrowserCrashed {
+    pub process_id: Option<u32>,
+    pub exit_status: Option<ExitStatus>,
+}
+
 impl Transport {
-    pub fn new(ws_url: String, process_id: Option<u32>) -> Result<Self, Error> {
+    pub fn new(ws_url: String, process: Option<ProcessHandle>) -> Result<Self, Error> {
+        let process_id = process.as_ref().map(ProcessHandle::id);
         let (messages_tx, messages_rx) = mpsc::channel();
         let web_socket_connection =
             Arc::new(WebSocketConnection::new(&ws_url, process_id, messages_tx)?);
@@ -81,6 +101,9 @@ impl Transport {
 
         let guarded_shutdown_tx = Mutex::new(shutdown_tx);
 
+        let shutting_down = Arc::new(AtomicBool::new(false));
+        let crash = Arc::new(Mutex::new(None));
+
         Self::handle_incoming_messages(
             messages_rx,
             Arc::clone(&waiting_call_registry),
@@ -88,7 +111,9 @@ impl Transport {
             Arc::clone(&open),
             Arc::clone(&web_socket_connection),
             shutdown_rx,
-            process_id,
+            Arc::clone(&shutting_down),
+            Arc::clone(&crash),
+            process,
         );
 
         Ok(Self {
@@ -98,6 +123,8 @@ impl Transport {
             open,
             call_id_counter: Arc::new(AtomicUsize::new(0)),
             loop_shutdown_tx: guarded_shutdown_tx,
+            shutting_down,
+            crash,
         })
     }
 
@@ -117,6 +144,9 @@ impl Transport {
     {
         // TODO: use get_mut to get exclusive access for entire block... maybe.
         if !self.open.load(Ordering::SeqCst) {
+            if let Some(crash) = self.get_crash() {
+                return Err(crash.into());
+            }
             return Err(ConnectionClosed {}.into());
         }
         let call_id = self.unique_call_id();
@@ -124,7 +154,12 @@ impl Transport {
 
         let message_text = serde_json::to_string(&call)?;
 
-        let response_rx = self.waiting_call_registry.register_call(call.id);
+        let response_rx = match &destination {
+            MethodDestination::Target(session_id) => self
+                .waiting_call_registry
+                .register_call_for_session(call.id, session_id.clone()),
+            MethodDestination::Browser => self.waiting_call_registry.register_call(call.id),
+        };
 
         match destination {
             MethodDestination::Target(session_id) => {
@@ -204,12 +239,35 @@ impl Transport {
         events_rx
     }
 
+    /// Stops forwarding events from the given session, e.g. because its target was destroyed.
+    pub fn stop_listening_to_target_events(&self, session_id: &SessionId) {
+        let mut listeners = self.listeners.lock().unwrap();
+        listeners.remove(&ListenerId::SessionId(session_id.clone()));
+    }
+
+    /// Fails any calls still waiting for a response from the given session with `error`.
+    pub fn cancel_calls_for_session<E>(&self, session_id: &SessionId, error: E)
+    where
+        E: Fail + Clone,
+    {
+        self.waiting_call_registry
+            .cancel_method_calls_for_session(session_id, error);
+    }
+
+    /// If the connection was lost because Chrome went away (rather than because we shut it down),
+    /// details about what happened.
+    pub fn get_crash(&self) -> Option<BrowserCrashed> {
+        self.crash.lock().unwrap().clone()
+    }
+
     pub fn shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
         self.web_socket_connection.shutdown();
         let shutdown_tx = self.loop_shutdown_tx.lock().unwrap();
         let _ = shutdown_tx.send(());
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn handle_incoming_messages(
         messages_rx: Receiver<protocol::Message>,
         waiting_call_registry: Arc<WaitingCallRegistry>,
@@ -217,13 +275,17 @@ impl Transport {
         open: Arc<AtomicBool>,
         conn: Arc<WebSocketConnection>,
         shutdown_rx: Receiver<()>,
-        process_id: Option<u32>,
+        shutting_down: Arc<AtomicBool>,
+        crash: Arc<Mutex<Option<BrowserCrashed>>>,
+        process: Option<ProcessHandle>,
     ) {
+        let process_id = process.as_ref().map(ProcessHandle::id);
         trace!("Starting handle_incoming_messages");
         std::thread::spawn(move || {
             trace!("Inside handle_incoming_messages thread");
             // this iterator calls .recv() under the hood, so can block thread forever
             // hence need for Connection Shutdown
+            let mut connection_lost = false;
             loop {
                 match shutdown_rx.try_recv() {
                     Ok(_) | Err(TryRecvError::Disconnected) => {
@@ -233,21 +295,19 @@ impl Transport {
                     Err(TryRecvError::Empty) => {}
                 }
                 match messages_rx.recv_timeout(Duration::from_millis(30_000)) {
-                    Err(recv_timeout_error) => {
-                        match recv_timeout_error {
-                            RecvTimeoutError::Timeout => {
-                                error!(
-                                    "Transport loop got a timeout while listening for messages (Chrome #{:?})",
-                                    process_id
-                                );
-                            }
-                            RecvTimeoutError::Disconnected => {
-                                error!(
-                                    "Transport loop got disconnected from WS's sender (Chrome #{:?})",
-                                    process_id
-                                );
-                            }
-                        }
+                    // An idle browser is not a dead one, so we keep waiting
+                    Err(RecvTimeoutError::Timeout) => {
+                        trace!(
+                            "Transport loop hasn't received any messages for a while (Chrome #{:?})",
+                            process_id
+                        );
+                    }
+                    Err(RecvTimeoutError::Disconnected) => {
+                        error!(
+                            "Transport loop got disconnected from WS's sender (Chrome #{:?})",
+                            process_id
+                        );
+                        connection_lost = true;
                         break;
                     }
                     Ok(message) => {
@@ -255,6 +315,7 @@ impl Transport {
                         match message {
                             Message::ConnectionShutdown => {
                                 info!("Received shutdown message");
+                                connection_lost = true;
                                 break;
                             }
                             Message::Response(response_to_browser_method_call) => {
@@ -282,8 +343,11 @@ impl Transport {
                                                     .unwrap()
                                                     .get(&ListenerId::SessionId(session_id))
                                                 {
-                                                    tx.send(target_event)
-                                                        .expect("Couldn't send event to listener");
+                                                    if tx.send(target_event).is_err() {
+                                                        trace!(
+                                                            "Target's event listener has gone away"
+                                                        );
+                                                    }
                                                 }
                                             }
 
@@ -331,7 +395,25 @@ impl Transport {
             conn.shutdown();
 
             open.store(false, Ordering::SeqCst);
-            waiting_call_registry.cancel_outstanding_method_calls();
+
+            if !connection_lost || shutting_down.load(Ordering::SeqCst) {
+                waiting_call_registry.cancel_outstanding_method_calls(ConnectionClosed {});
+            } else {
+                // Chrome might take a moment to actually exit after closing the connection
+                let exit_status = process.and_then(|process| {
+                    util::Wait::with_timeout(Duration::from_secs(2))
+                        .until(|| process.try_exit_status())
+                        .ok()
+                });
+                let browser_crashed = BrowserCrashed {
+                    process_id,
+                    exit_status,
+                };
+                error!("{}", browser_crashed);
+                *crash.lock().unwrap() = Some(browser_crashed.clone());
+                waiting_call_registry.cancel_outstanding_method_calls(browser_crashed);
+            }
+
             let mut listeners = listeners.lock().unwrap();
             *listeners = HashMap::new();
             info!("cleared listeners, I think");
@@ -1,10 +1,10 @@
-use failure::Error;
+use failure::{Error, Fail};
 use log::*;
 use std::collections::HashMap;
 use std::sync::mpsc;
 use std::sync::Mutex;
 
-use super::ConnectionClosed;
+use super::SessionId;
 use crate::protocol::{CallId, Response};
 
 trait IdentifiableResponse {
@@ -14,6 +14,7 @@ trait IdentifiableResponse {
 #[derive(Debug)]
 pub struct WaitingCallRegistry {
     calls: Mutex<HashMap<CallId, mpsc::Sender<Result<Response, Error>>>>,
+    call_sessions: Mutex<HashMap<CallId, SessionId>>,
 }
 
 impl IdentifiableResponse for Response {
@@ -25,8 +26,12 @@ impl IdentifiableResponse for Response {
 impl Default for WaitingCallRegistry {
     fn default() -> Self {
         let calls = Mutex::new(HashMap::new());
+        let call_sessions = Mutex::new(HashMap::new());
 
-        Self { calls }
+        Self {
+            calls,
+            call_sessions,
+        }
     }
 }
 
@@ -37,9 +42,22 @@ impl WaitingCallRegistry {
 
     pub fn resolve_call(&self, response: Response) -> Result<(), Error> {
         trace!("Resolving call");
+        self.call_sessions
+            .lock()
+            .unwrap()
+            .remove(&response.call_id());
         let waiting_call_tx: mpsc::Sender<Result<Response, Error>> = {
             let mut waiting_calls = self.calls.lock().unwrap();
-            waiting_calls.remove(&response.call_id()).unwrap()
+            if let Some(tx) = waiting_calls.remove(&response.call_id()) {
+                tx
+            } else {
+                // e.g. the call was already cancelled because its tab crashed
+                trace!(
+                    "No one is waiting for call {:?} anymore",
+                    response.call_id()
+                );
+                return Ok(());
+            }
         };
         waiting_call_tx.send(Ok(response))?;
         Ok(())
@@ -53,25 +71,68 @@ impl WaitingCallRegistry {
         rx
     }
 
+    /// Like `register_call`, but also remembers which target session the call was sent to, so
+    /// that it can be cancelled with `cancel_method_calls_for_session`.
+    pub fn register_call_for_session(
+        &self,
+        call_id: CallId,
+        session_id: SessionId,
+    ) -> mpsc::Receiver<Result<Response, Error>> {
+        self.call_sessions
+            .lock()
+            .unwrap()
+            .insert(call_id, session_id);
+        self.register_call(call_id)
+    }
+
     pub fn unregister_call(&self, call_id: CallId) {
         trace!("Deregistering call");
+        self.call_sessions.lock().unwrap().remove(&call_id);
+        let mut calls = self.calls.lock().unwrap();
+        calls.remove(&call_id);
+    }
+
+    /// Fails the calls waiting for a response from the given session, e.g. because its target
+    /// crashed and so will never respond.
+    pub fn cancel_method_calls_for_session<E>(&self, session_id: &SessionId, error: E)
+    where
+        E: Fail + Clone,
+    {
+        trace!("Cancelling method calls for session {:?}", session_id);
+        let mut call_sessions = self.call_sessions.lock().unwrap();
+        let call_ids: Vec<CallId> = call_sessions
+            .iter()
+            .filter(|(_, call_session_id)| *call_session_id == session_id)
+            .map(|(call_id, _)| *call_id)
+            .collect();
+
         let mut calls = self.calls.lock().unwrap();
-        calls.remove(&call_id).unwrap();
+        for call_id in call_ids {
+            call_sessions.remove(&call_id);
+            if let Some(sender) = calls.remove(&call_id) {
+                if sender.send(Err(error.clone().into())).is_err() {
+                    trace!("Call {:?} was no longer being waited for", call_id);
+                }
+            }
+        }
     }
 
-    // TODO: make it so we can pass in whatever error we want here
-    // to make it less dependent on browser::transport
-    pub fn cancel_outstanding_method_calls(&self) {
+    /// Fails every call that's still waiting for a response with (a copy of) the given error.
+    pub fn cancel_outstanding_method_calls<E>(&self, error: E)
+    where
+        E: Fail + Clone,
+    {
         trace!("Cancelling outstanding method calls");
         let calls = self.calls.lock().unwrap();
         for (call_id, sender) in calls.iter() {
             trace!(
-                "Telling waiting method call {:?} that the connection closed",
-                call_id
+                "Telling waiting method call {:?} that it failed: {}",
+                call_id,
+                error
             );
-            if let Err(e) = sender.send(Err(ConnectionClosed {}.into())) {
+            if let Err(e) = sender.send(Err(error.clone().into())) {
                 trace!(
-                    "Couldn't send ConnectionClosed to waiting method call: {:?} because {:?}",
+                    "Couldn't send error to waiting method call: {:?} because {:?}",
                     call_id,
                     e
                 );
@@ -83,6 +144,7 @@ impl WaitingCallRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::browser::transport::BrowserCrashed;
     use serde_json::json;
 
     #[test]
@@ -114,4 +176,63 @@ mod tests {
         assert_eq!(resp2_clone, call_rx2.recv().unwrap().unwrap());
         assert_eq!(resp_clone, call_rx.recv().unwrap().unwrap());
     }
+
+    #[test]
+    fn cancel_outstanding_calls_with_given_error() {
+        env_logger::try_init().unwrap_or(());
+
+        let waiting_calls = WaitingCallRegistry::new();
+
+        let call_rx = waiting_calls.register_call(1);
+        let call_rx2 = waiting_calls.register_call(2);
+
+        waiting_calls.cancel_outstanding_method_calls(BrowserCrashed {
+            process_id: Some(42),
+            exit_status: None,
+        });
+
+        for rx in &[call_rx, call_rx2] {
+            let error = rx.recv().unwrap().unwrap_err();
+            let crashed = error.downcast::<BrowserCrashed>().unwrap();
+            assert_eq!(Some(42), crashed.process_id);
+        }
+    }
+
+    #[test]
+    fn cancel_calls_for_one_session_only() {
+        env_logger::try_init().unwrap_or(());
+
+        let waiting_calls = WaitingCallRegistry::new();
+        let crashed_session: SessionId = "crashed".to_string().into();
+
+        let crashed_rx = waiting_calls.register_call_for_session(1, crashed_session.clone());
+        let healthy_rx = waiting_calls.register_call_for_session(2, "healthy".to_string().into());
+
+        waiting_calls.cancel_method_calls_for_session(
+            &crashed_session,
+            BrowserCrashed {
+                process_id: None,
+                exit_status: None,
+            },
+        );
+        assert!(crashed_rx.recv().unwrap().is_err());
+        assert!(healthy_rx.try_recv().is_err());
+
+        // a late response to the cancelled call is ignored
+        waiting_calls
+            .resolve_call(Response {
+                call_id: 1,
+                result: Some(json! {true}),
+                error: None,
+            })
+            .unwrap();
+        waiting_calls
+            .resolve_call(Response {
+                call_id: 2,
+                result: Some(json! {true}),
+                error: None,
+            })
+            .unwrap();
+        assert!(healthy_rx.recv().unwrap().is_ok());
+    }
 }
@@ -17,6 +17,7 @@ extern crate derive_builder;
 pub mod browser;
 pub mod protocol;
 pub mod util;
+pub mod visual_diff;
 
 pub use browser::{Browser, LaunchOptionsBuilder, Tab};
 
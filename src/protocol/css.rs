@@ -0,0 +1,125 @@
+use serde::Deserialize;
+
+/// Information about a stylesheet, sent when it's added to the page.
+/// See https://chromedevtools.github.io/devtools-protocol/tot/CSS#type-CSSStyleSheetHeader
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleSheetHeader {
+    pub style_sheet_id: String,
+    pub frame_id: String,
+    /// The URL the stylesheet was loaded from, or the page's URL for `<style>` elements
+    pub source_url: String,
+    /// "regular" for the page's own stylesheets, or "injected", "user-agent" or "inspector"
+    pub origin: String,
+    pub title: String,
+    pub disabled: bool,
+    /// Whether the stylesheet comes from a `<style>` element (or `document.write`)
+    pub is_inline: bool,
+    pub start_line: f64,
+    pub start_column: f64,
+    /// In characters
+    pub length: Option<f64>,
+}
+
+/// Whether a rule in a stylesheet has been used to style anything.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleUsage {
+    pub style_sheet_id: String,
+    /// Offset of the rule's start in the stylesheet's text, in UTF-16 code units
+    pub start_offset: f64,
+    /// Offset of the rule's end in the stylesheet's text, in UTF-16 code units
+    pub end_offset: f64,
+    pub used: bool,
+}
+
+pub mod methods {
+    use crate::protocol::Method;
+    use serde::{Deserialize, Serialize};
+
+    /// Requires the DOM domain to be enabled.
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Enable {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EnableReturnObject {}
+    impl Method for Enable {
+        const NAME: &'static str = "CSS.enable";
+        type ReturnObject = EnableReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Disable {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DisableReturnObject {}
+    impl Method for Disable {
+        const NAME: &'static str = "CSS.disable";
+        type ReturnObject = DisableReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StartRuleUsageTracking {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StartRuleUsageTrackingReturnObject {}
+    impl Method for StartRuleUsageTracking {
+        const NAME: &'static str = "CSS.startRuleUsageTracking";
+        type ReturnObject = StartRuleUsageTrackingReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StopRuleUsageTracking {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StopRuleUsageTrackingReturnObject {
+        pub rule_usage: Vec<super::RuleUsage>,
+    }
+    impl Method for StopRuleUsageTracking {
+        const NAME: &'static str = "CSS.stopRuleUsageTracking";
+        type ReturnObject = StopRuleUsageTrackingReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetStyleSheetText<'a> {
+        pub style_sheet_id: &'a str,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetStyleSheetTextReturnObject {
+        pub text: String,
+    }
+    impl<'a> Method for GetStyleSheetText<'a> {
+        const NAME: &'static str = "CSS.getStyleSheetText";
+        type ReturnObject = GetStyleSheetTextReturnObject;
+    }
+}
+
+pub mod events {
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, Clone)]
+    pub struct StyleSheetAddedEvent {
+        pub params: StyleSheetAddedParams,
+    }
+    #[derive(Deserialize, Debug, Clone)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StyleSheetAddedParams {
+        pub header: super::StyleSheetHeader,
+    }
+
+    #[derive(Deserialize, Debug, Clone)]
+    pub struct StyleSheetRemovedEvent {
+        pub params: StyleSheetRemovedParams,
+    }
+    #[derive(Deserialize, Debug, Clone)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StyleSheetRemovedParams {
+        pub style_sheet_id: String,
+    }
+}
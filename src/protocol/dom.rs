@@ -131,6 +131,17 @@ pub mod methods {
     use crate::protocol::Method;
     use serde::{Deserialize, Serialize};
 
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Enable {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EnableReturnObject {}
+    impl Method for Enable {
+        const NAME: &'static str = "DOM.enable";
+        type ReturnObject = EnableReturnObject;
+    }
+
     #[derive(Serialize, Debug)]
     #[serde(rename_all = "camelCase")]
     pub struct GetDocument {
@@ -255,6 +266,21 @@ pub mod methods {
         type ReturnObject = ResolveNodeReturnObject;
     }
 
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RequestNode<'a> {
+        pub object_id: &'a str,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RequestNodeReturnObject {
+        pub node_id: super::NodeId,
+    }
+    impl<'a> Method for RequestNode<'a> {
+        const NAME: &'static str = "DOM.requestNode";
+        type ReturnObject = RequestNodeReturnObject;
+    }
+
     #[derive(Serialize, Debug)]
     #[serde(rename_all = "camelCase")]
     pub struct GetContentQuads<'a> {
@@ -304,4 +330,21 @@ pub mod methods {
         type ReturnObject = GetBoxModelReturnObject;
     }
 
+    /// Experimental, available since Chrome 76
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ScrollIntoViewIfNeeded<'a> {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub node_id: Option<super::NodeId>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub backend_node_id: Option<super::NodeId>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub object_id: Option<&'a str>,
+    }
+    #[derive(Debug, Deserialize)]
+    pub struct ScrollIntoViewIfNeededReturnObject {}
+    impl<'a> Method for ScrollIntoViewIfNeeded<'a> {
+        const NAME: &'static str = "DOM.scrollIntoViewIfNeeded";
+        type ReturnObject = ScrollIntoViewIfNeededReturnObject;
+    }
 }
@@ -0,0 +1,58 @@
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct RGBA {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    /// Between 0 (transparent) and 1 (opaque)
+    pub a: f64,
+}
+
+pub mod methods {
+    use crate::protocol::Method;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Debug, Default, Clone)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetDeviceMetricsOverride {
+        pub width: u32,
+        pub height: u32,
+        /// 0 disables the override
+        pub device_scale_factor: f64,
+        pub mobile: bool,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetDeviceMetricsOverrideReturnObject {}
+    impl Method for SetDeviceMetricsOverride {
+        const NAME: &'static str = "Emulation.setDeviceMetricsOverride";
+        type ReturnObject = SetDeviceMetricsOverrideReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ClearDeviceMetricsOverride {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ClearDeviceMetricsOverrideReturnObject {}
+    impl Method for ClearDeviceMetricsOverride {
+        const NAME: &'static str = "Emulation.clearDeviceMetricsOverride";
+        type ReturnObject = ClearDeviceMetricsOverrideReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetDefaultBackgroundColorOverride {
+        /// `None` removes the override
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub color: Option<super::RGBA>,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetDefaultBackgroundColorOverrideReturnObject {}
+    impl Method for SetDefaultBackgroundColorOverride {
+        const NAME: &'static str = "Emulation.setDefaultBackgroundColorOverride";
+        type ReturnObject = SetDefaultBackgroundColorOverrideReturnObject;
+    }
+}
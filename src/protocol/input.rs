@@ -13,6 +13,18 @@ pub mod methods {
         pub button: Option<&'a str>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub click_count: Option<u8>,
+        /// Bit field: Alt=1, Ctrl=2, Meta/Command=4, Shift=8
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub modifiers: Option<u32>,
+        /// Bit field of the buttons held down: Left=1, Right=2, Middle=4, Back=8, Forward=16
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub buttons: Option<u32>,
+        /// X delta in CSS pixels for mouseWheel events
+        #[serde(rename = "deltaX", skip_serializing_if = "Option::is_none")]
+        pub delta_x: Option<f64>,
+        /// Y delta in CSS pixels for mouseWheel events
+        #[serde(rename = "deltaY", skip_serializing_if = "Option::is_none")]
+        pub delta_y: Option<f64>,
     }
     impl<'a> Default for DispatchMouseEvent<'a> {
         fn default() -> Self {
@@ -22,6 +34,10 @@ pub mod methods {
                 y: 0.0,
                 button: None,
                 click_count: None,
+                modifiers: None,
+                buttons: None,
+                delta_x: None,
+                delta_y: None,
             }
         }
     }
@@ -46,6 +62,11 @@ pub mod methods {
         pub code: Option<&'a str>,
         pub windows_virtual_key_code: u8,
         pub native_virtual_key_code: u8,
+        /// Bit field: Alt=1, Ctrl=2, Meta/Command=4, Shift=8
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub modifiers: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub location: Option<u8>,
     }
     #[derive(Debug, Deserialize)]
     #[serde(rename_all = "camelCase")]
@@ -54,4 +75,160 @@ pub mod methods {
         const NAME: &'static str = "Input.dispatchKeyEvent";
         type ReturnObject = DispatchKeyEventReturnObject;
     }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct InsertText<'a> {
+        pub text: &'a str,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct InsertTextReturnObject {}
+    impl<'a> Method for InsertText<'a> {
+        const NAME: &'static str = "Input.insertText";
+        type ReturnObject = InsertTextReturnObject;
+    }
+
+    /// Experimental, and only available in recent versions of Chrome
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ImeSetComposition<'a> {
+        pub text: &'a str,
+        pub selection_start: u32,
+        pub selection_end: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub replacement_start: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub replacement_end: Option<u32>,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ImeSetCompositionReturnObject {}
+    impl<'a> Method for ImeSetComposition<'a> {
+        const NAME: &'static str = "Input.imeSetComposition";
+        type ReturnObject = ImeSetCompositionReturnObject;
+    }
+
+    #[derive(Serialize, Debug, Clone, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TouchPoint {
+        pub x: f64,
+        pub y: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub radius_x: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub radius_y: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub rotation_angle: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub force: Option<f64>,
+        /// Identifies the touch point across touchStart / touchMove / touchEnd events
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub id: Option<u32>,
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DispatchTouchEvent<'a> {
+        /// One of touchStart, touchMove, touchEnd and touchCancel
+        #[serde(rename = "type")]
+        pub event_type: &'a str,
+        /// Must be empty for touchEnd and touchCancel
+        pub touch_points: Vec<TouchPoint>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub modifiers: Option<u32>,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DispatchTouchEventReturnObject {}
+    impl<'a> Method for DispatchTouchEvent<'a> {
+        const NAME: &'static str = "Input.dispatchTouchEvent";
+        type ReturnObject = DispatchTouchEventReturnObject;
+    }
+
+    /// Experimental. Responds once the gesture has finished.
+    #[derive(Serialize, Debug, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SynthesizePinchGesture<'a> {
+        pub x: f64,
+        pub y: f64,
+        /// Less than 1.0 zooms out, more than 1.0 zooms in
+        pub scale_factor: f64,
+        /// Pixels per second, 800 by default
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub relative_speed: Option<u32>,
+        /// "touch", "mouse" or "default"
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub gesture_source_type: Option<&'a str>,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SynthesizePinchGestureReturnObject {}
+    impl<'a> Method for SynthesizePinchGesture<'a> {
+        const NAME: &'static str = "Input.synthesizePinchGesture";
+        type ReturnObject = SynthesizePinchGestureReturnObject;
+    }
+
+    /// Experimental. Responds once the gesture has finished.
+    #[derive(Serialize, Debug, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SynthesizeScrollGesture<'a> {
+        pub x: f64,
+        pub y: f64,
+        /// Negative values scroll right
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub x_distance: Option<f64>,
+        /// Negative values scroll down
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub y_distance: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub x_overscroll: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub y_overscroll: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub prevent_fling: Option<bool>,
+        /// Pixels per second, 800 by default
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub speed: Option<u32>,
+        /// "touch", "mouse" or "default"
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub gesture_source_type: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub repeat_count: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub repeat_delay_ms: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub interaction_marker_name: Option<&'a str>,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SynthesizeScrollGestureReturnObject {}
+    impl<'a> Method for SynthesizeScrollGesture<'a> {
+        const NAME: &'static str = "Input.synthesizeScrollGesture";
+        type ReturnObject = SynthesizeScrollGestureReturnObject;
+    }
+
+    /// Experimental. Responds once the gesture has finished.
+    #[derive(Serialize, Debug, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SynthesizeTapGesture<'a> {
+        pub x: f64,
+        pub y: f64,
+        /// Milliseconds between touch down and up, 50 by default
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub duration: Option<u32>,
+        /// 2 for a double tap, 1 by default
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub tap_count: Option<u32>,
+        /// "touch", "mouse" or "default"
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub gesture_source_type: Option<&'a str>,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SynthesizeTapGestureReturnObject {}
+    impl<'a> Method for SynthesizeTapGesture<'a> {
+        const NAME: &'static str = "Input.synthesizeTapGesture";
+        type ReturnObject = SynthesizeTapGestureReturnObject;
+    }
 }
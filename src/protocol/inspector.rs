@@ -0,0 +1,27 @@
+pub mod events {
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, Clone)]
+    pub struct TargetCrashedEvent {
+        pub params: TargetCrashedParams,
+    }
+
+    #[derive(Deserialize, Debug, Clone)]
+    pub struct TargetCrashedParams {}
+}
+
+pub mod methods {
+    use crate::protocol::Method;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Enable {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EnableReturnObject {}
+    impl Method for Enable {
+        const NAME: &'static str = "Inspector.enable";
+        type ReturnObject = EnableReturnObject;
+    }
+}
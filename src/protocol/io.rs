@@ -0,0 +1,40 @@
+pub mod methods {
+    use crate::protocol::Method;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Read<'a> {
+        pub handle: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub offset: Option<u64>,
+        /// Maximum number of bytes to read
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub size: Option<u32>,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ReadReturnObject {
+        #[serde(default)]
+        pub base64_encoded: bool,
+        pub data: String,
+        pub eof: bool,
+    }
+    impl<'a> Method for Read<'a> {
+        const NAME: &'static str = "IO.read";
+        type ReturnObject = ReadReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Close<'a> {
+        pub handle: &'a str,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CloseReturnObject {}
+    impl<'a> Method for Close<'a> {
+        const NAME: &'static str = "IO.close";
+        type ReturnObject = CloseReturnObject;
+    }
+}
@@ -7,13 +7,18 @@ use serde_json::Value;
 use std::fmt::Debug;
 
 pub mod browser;
+pub mod css;
 pub mod debugger;
 pub mod dom;
+pub mod emulation;
 pub mod input;
+pub mod inspector;
+pub mod io;
 pub mod network;
 pub mod page;
 pub mod profiler;
 pub mod runtime;
+pub mod storage;
 pub mod target;
 
 pub type CallId = usize;
@@ -62,6 +67,13 @@ pub struct RemoteError {
     pub message: String,
 }
 
+impl RemoteError {
+    /// Whether Chrome didn't recognise the method, e.g. because it's too new for this version.
+    pub fn is_method_not_found(&self) -> bool {
+        self.code == -32601
+    }
+}
+
 #[derive(Deserialize, Debug, PartialEq, Clone)]
 pub struct Response {
     #[serde(rename(deserialize = "id"))]
@@ -97,6 +109,10 @@ pub enum Event {
     TargetCreated(target::events::TargetCreatedEvent),
     #[serde(rename = "Target.targetDestroyed")]
     TargetDestroyed(target::events::TargetDestroyedEvent),
+    #[serde(rename = "Target.targetCrashed")]
+    TargetCrashed(target::events::TargetCrashedEvent),
+    #[serde(rename = "Inspector.targetCrashed")]
+    InspectorTargetCrashed(inspector::events::TargetCrashedEvent),
     #[serde(rename = "Page.frameStartedLoading")]
     FrameStartedLoading(page::events::FrameStartedLoadingEvent),
     #[serde(rename = "Page.frameNavigated")]
@@ -105,6 +121,12 @@ pub enum Event {
     FrameStoppedLoading(page::events::FrameStoppedLoadingEvent),
     #[serde(rename = "Page.lifecycleEvent")]
     Lifecycle(page::events::LifecycleEvent),
+    #[serde(rename = "Page.screencastFrame")]
+    ScreencastFrame(page::events::ScreencastFrameEvent),
+    #[serde(rename = "CSS.styleSheetAdded")]
+    StyleSheetAdded(css::events::StyleSheetAddedEvent),
+    #[serde(rename = "CSS.styleSheetRemoved")]
+    StyleSheetRemoved(css::events::StyleSheetRemovedEvent),
     #[serde(rename = "Network.requestIntercepted")]
     RequestIntercepted(network::events::RequestInterceptedEvent),
 }
@@ -190,6 +212,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_crash_events() {
+        env_logger::try_init().unwrap_or(());
+
+        let target_crashed = json!({
+            "method": "Target.targetCrashed",
+            "params": {
+                "targetId": "26DEBCB2A45BEFC67A84012AC32C8B2A",
+                "status": "crashed",
+                "errorCode": 11
+            }
+        });
+        match serde_json::from_value(target_crashed).unwrap() {
+            Event::TargetCrashed(ev) => assert_eq!("crashed", ev.params.status),
+            _ => panic!("Failed to parse Target.targetCrashed"),
+        }
+
+        let inspector_crashed = json!({
+            "method": "Inspector.targetCrashed",
+            "params": {}
+        });
+        match serde_json::from_value(inspector_crashed).unwrap() {
+            Event::InspectorTargetCrashed(_) => {}
+            _ => panic!("Failed to parse Inspector.targetCrashed"),
+        }
+    }
+
     #[test]
     fn easy_parse_messages() {
         env_logger::try_init().unwrap_or(());
@@ -199,4 +199,14 @@ pub mod methods {
         type ReturnObject = GetResponseBodyForInterceptionReturnObject;
     }
 
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ClearBrowserCookies {}
+    #[derive(Deserialize, Debug, Clone)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ClearBrowserCookiesReturnObject {}
+    impl Method for ClearBrowserCookies {
+        const NAME: &'static str = "Network.clearBrowserCookies";
+        type ReturnObject = ClearBrowserCookiesReturnObject;
+    }
 }
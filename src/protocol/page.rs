@@ -18,6 +18,7 @@ pub struct Frame {
 pub(crate) enum InternalScreenshotFormat {
     JPEG,
     PNG,
+    WEBP,
 }
 
 /// Viewport for capturing screenshot.
@@ -35,15 +36,152 @@ pub struct Viewport {
     pub scale: f64,
 }
 
+/// Layout viewport position and dimensions, in CSS pixels
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutViewport {
+    pub page_x: f64,
+    pub page_y: f64,
+    pub client_width: f64,
+    pub client_height: f64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
 /// The format a screenshot will be captured in
 #[derive(Debug, Clone)]
 pub enum ScreenshotFormat {
     /// Optionally compression quality from range [0..100]
     JPEG(Option<u8>),
     PNG,
+    /// Optionally compression quality from range [0..100]
+    WEBP(Option<u8>),
+}
+
+/// Options for `Tab::capture_screenshot_with_options`
+///
+/// ```rust
+/// use headless_chrome::protocol::page::{ScreenshotFormat, ScreenshotOptionsBuilder};
+/// let options = ScreenshotOptionsBuilder::default()
+///     .format(ScreenshotFormat::JPEG(Some(80)))
+///     .full_page(true)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Builder)]
+pub struct ScreenshotOptions {
+    /// Defaults to PNG.
+    #[builder(default = "ScreenshotFormat::PNG")]
+    pub format: ScreenshotFormat,
+    /// Capture only this region of the page.
+    #[builder(default = "None")]
+    pub clip: Option<Viewport>,
+    /// Capture from the surface rather than the view. Defaults to true.
+    #[builder(default = "true")]
+    pub from_surface: bool,
+    /// Capture the whole scrollable page rather than just what's in the viewport, by resizing
+    /// the viewport to fit the page. Ignored if `clip` is given.
+    #[builder(default = "false")]
+    pub full_page: bool,
+    /// Capture parts of `clip` that lie outside of the viewport, without resizing it. Requires
+    /// Chrome 87 or newer.
+    #[builder(default = "false")]
+    pub capture_beyond_viewport: bool,
+    /// Render the page at this many device pixels per CSS pixel while capturing, e.g. 2 for
+    /// screenshots as sharp as on a retina display. Defaults to the browser's own.
+    #[builder(default = "None")]
+    pub device_scale_factor: Option<f64>,
+    /// Make the page's default white background transparent, for PNG and WebP screenshots of
+    /// elements that don't have a background of their own.
+    #[builder(default = "false")]
+    pub omit_background: bool,
 }
 
-#[derive(Serialize, Debug)]
+/// A length in the units commonly used for paper, which Chrome is given in inches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Mm(f32),
+    Cm(f32),
+    In(f32),
+    /// CSS pixels, of which there are 96 to an inch
+    Px(f32),
+}
+
+impl Length {
+    pub fn to_inches(self) -> f32 {
+        match self {
+            Length::Mm(mm) => mm / 25.4,
+            Length::Cm(cm) => cm / 2.54,
+            Length::In(inches) => inches,
+            Length::Px(px) => px / 96.0,
+        }
+    }
+}
+
+impl Serialize for Length {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f32(self.to_inches())
+    }
+}
+
+/// Standard paper sizes, in portrait orientation except for `Ledger`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaperFormat {
+    Letter,
+    Legal,
+    Tabloid,
+    /// Tabloid in landscape orientation
+    Ledger,
+    A0,
+    A1,
+    A2,
+    A3,
+    A4,
+    A5,
+    A6,
+}
+
+impl PaperFormat {
+    /// The width and height of the paper.
+    pub fn size(self) -> (Length, Length) {
+        match self {
+            PaperFormat::Letter => (Length::In(8.5), Length::In(11.0)),
+            PaperFormat::Legal => (Length::In(8.5), Length::In(14.0)),
+            PaperFormat::Tabloid => (Length::In(11.0), Length::In(17.0)),
+            PaperFormat::Ledger => (Length::In(17.0), Length::In(11.0)),
+            PaperFormat::A0 => (Length::Mm(841.0), Length::Mm(1189.0)),
+            PaperFormat::A1 => (Length::Mm(594.0), Length::Mm(841.0)),
+            PaperFormat::A2 => (Length::Mm(420.0), Length::Mm(594.0)),
+            PaperFormat::A3 => (Length::Mm(297.0), Length::Mm(420.0)),
+            PaperFormat::A4 => (Length::Mm(210.0), Length::Mm(297.0)),
+            PaperFormat::A5 => (Length::Mm(148.0), Length::Mm(210.0)),
+            PaperFormat::A6 => (Length::Mm(105.0), Length::Mm(148.0)),
+        }
+    }
+}
+
+/// Options for `Tab::print_to_pdf`. Anything left unset is up to Chrome, which prints on
+/// Letter paper with margins of about 1cm.
+///
+/// ```rust
+/// use headless_chrome::protocol::page::{Length, PaperFormat, PrintToPdfOptionsBuilder};
+/// let options = PrintToPdfOptionsBuilder::default()
+///     .paper_format(PaperFormat::A4)
+///     .margins(Length::Mm(20.0))
+///     .page_ranges(Some("1-3, 5".to_string()))
+///     .footer("Page {pageNumber} of {totalPages}")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Serialize, Debug, Clone, Default, Builder)]
+#[builder(default, build_fn(validate = "Self::validate"))]
 #[serde(rename_all = "camelCase")]
 pub struct PrintToPdfOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -52,32 +190,184 @@ pub struct PrintToPdfOptions {
     pub display_header_footer: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub print_background: Option<bool>,
+    /// Between 0.1 and 2.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scale: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub paper_width: Option<f32>,
+    pub paper_width: Option<Length>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub paper_height: Option<f32>,
+    pub paper_height: Option<Length>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub margin_top: Option<f32>,
+    pub margin_top: Option<Length>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub margin_bottom: Option<f32>,
+    pub margin_bottom: Option<Length>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub margin_left: Option<f32>,
+    pub margin_left: Option<Length>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub margin_right: Option<f32>,
+    pub margin_right: Option<Length>,
+    /// One-based pages to print, e.g. "1-5, 8, 11-13". Everything is printed by default.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page_ranges: Option<String>,
+    /// Whether to silently ignore page ranges like "5-3", rather than failing.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub ignore_invalid_page_ranges: Option<String>,
+    pub ignore_invalid_page_ranges: Option<bool>,
+    /// HTML for the header, see `header_footer_template`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub header_template: Option<String>,
+    /// HTML for the footer, see `header_footer_template`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub footer_template: Option<String>,
+    /// Whether to use the page size given by the page's CSS `@page` rule, if any, rather than
+    /// the paper size.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prefer_css_page_size: Option<bool>,
 }
 
+impl PrintToPdfOptionsBuilder {
+    /// Sets the paper width and height to those of `format`.
+    pub fn paper_format(&mut self, format: PaperFormat) -> &mut Self {
+        let (width, height) = format.size();
+        self.paper_width = Some(Some(width));
+        self.paper_height = Some(Some(height));
+        self
+    }
+
+    /// Sets all four margins to `margin`.
+    pub fn margins(&mut self, margin: Length) -> &mut Self {
+        self.margin_top = Some(Some(margin));
+        self.margin_bottom = Some(Some(margin));
+        self.margin_left = Some(Some(margin));
+        self.margin_right = Some(Some(margin));
+        self
+    }
+
+    /// Prints `text` at the top of every page, in a small centered font. See
+    /// `header_footer_template` for the placeholders it can contain.
+    ///
+    /// The header is drawn within the top margin, so that needs to be big enough to fit it.
+    pub fn header(&mut self, text: &str) -> &mut Self {
+        self.header_template = Some(Some(header_footer_template(text)));
+        self.display_header_footer = Some(Some(true));
+        // otherwise Chrome prints its own footer, with the URL and page numbers
+        if self.footer_template.is_none() {
+            self.footer_template = Some(Some("<span></span>".to_string()));
+        }
+        self
+    }
+
+    /// Prints `text` at the bottom of every page, in a small centered font. See
+    /// `header_footer_template` for the placeholders it can contain.
+    ///
+    /// The footer is drawn within the bottom margin, so that needs to be big enough to fit it.
+    pub fn footer(&mut self, text: &str) -> &mut Self {
+        self.footer_template = Some(Some(header_footer_template(text)));
+        self.display_header_footer = Some(Some(true));
+        // otherwise Chrome prints its own header, with the date and title
+        if self.header_template.is_none() {
+            self.header_template = Some(Some("<span></span>".to_string()));
+        }
+        self
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(Some(scale)) = self.scale {
+            if !(0.1..=2.0).contains(&scale) {
+                return Err(format!("Scale must be between 0.1 and 2, not {}", scale));
+            }
+        }
+        if let Some(Some(ref page_ranges)) = self.page_ranges {
+            let ignore_invalid = self.ignore_invalid_page_ranges == Some(Some(true));
+            validate_page_ranges(page_ranges, ignore_invalid)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `page_ranges` looks like "1-5, 8, 11-13", where ranges may also be open ended
+/// ("3-" or "-3"). Ranges that start after they end are only allowed if `ignore_invalid`.
+fn validate_page_ranges(page_ranges: &str, ignore_invalid: bool) -> Result<(), String> {
+    let page_number = |number: &str| -> Result<Option<u32>, String> {
+        match number.trim() {
+            "" => Ok(None),
+            number => match number.parse() {
+                Ok(0) | Err(_) => Err(format!(
+                    "Invalid page number {:?} in page ranges {:?}",
+                    number, page_ranges
+                )),
+                Ok(number) => Ok(Some(number)),
+            },
+        }
+    };
+
+    for range in page_ranges.split(',') {
+        let mut bounds = range.splitn(2, '-');
+        let start = page_number(bounds.next().unwrap_or(""))?;
+        let end = match bounds.next() {
+            Some(end) => page_number(end)?,
+            None => start,
+        };
+        match (start, end) {
+            (None, None) => return Err(format!("Empty page range in {:?}", page_ranges)),
+            (Some(start), Some(end)) if start > end && !ignore_invalid => {
+                return Err(format!(
+                    "Page range {:?} starts after it ends",
+                    range.trim()
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Turns `text` into HTML for a header or footer template, replacing these placeholders with
+/// the elements Chrome fills in for each page:
+///
+/// * `{pageNumber}`: the current page number
+/// * `{totalPages}`: the number of pages in the document
+/// * `{date}`: the date the document was printed
+/// * `{title}`: the document's title
+/// * `{url}`: the document's URL
+///
+/// The rest of `text` is escaped. Templates don't inherit any styles from the page, and
+/// Chrome's default font size for them is too small to read, so the text is given a font size
+/// of 10px.
+pub fn header_footer_template(text: &str) -> String {
+    const CLASSES: [&str; 5] = ["pageNumber", "totalPages", "date", "title", "url"];
+
+    let mut html = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let placeholder = CLASSES.iter().find_map(|class| {
+            let placeholder = format!("{{{}}}", class);
+            if rest.starts_with(&placeholder) {
+                Some((class, placeholder.len()))
+            } else {
+                None
+            }
+        });
+        if let Some((class, length)) = placeholder {
+            html.push_str(&format!("<span class=\"{}\"></span>", class));
+            rest = &rest[length..];
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            c => html.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    format!(
+        "<div style=\"font-size: 10px; width: 100%; text-align: center;\">{}</div>",
+        html
+    )
+}
+
 pub mod events {
     use serde::Deserialize;
     #[derive(Deserialize, Debug, Clone)]
@@ -122,6 +412,32 @@ pub mod events {
     pub struct FrameStoppedLoadingParams {
         pub frame_id: String,
     }
+
+    #[derive(Deserialize, Debug, Clone)]
+    pub struct ScreencastFrameEvent {
+        pub params: ScreencastFrameParams,
+    }
+    #[derive(Deserialize, Debug, Clone)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ScreencastFrameParams {
+        /// Base64-encoded image
+        pub data: String,
+        pub metadata: ScreencastFrameMetadata,
+        /// To acknowledge the frame with
+        pub session_id: u32,
+    }
+    #[derive(Deserialize, Debug, Clone)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ScreencastFrameMetadata {
+        pub offset_top: f64,
+        pub page_scale_factor: f64,
+        pub device_width: f64,
+        pub device_height: f64,
+        pub scroll_offset_x: f64,
+        pub scroll_offset_y: f64,
+        /// Seconds since the epoch
+        pub timestamp: Option<f64>,
+    }
 }
 
 pub mod methods {
@@ -138,6 +454,8 @@ pub mod methods {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub clip: Option<super::Viewport>,
         pub from_surface: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub capture_beyond_viewport: Option<bool>,
     }
     #[derive(Debug, Deserialize)]
     #[serde(rename_all = "camelCase")]
@@ -149,16 +467,86 @@ pub mod methods {
         type ReturnObject = CaptureScreenshotReturnObject;
     }
 
+    #[derive(Serialize, Debug, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StartScreencast {
+        /// "jpeg" or "png"
+        pub format: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub quality: Option<u8>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub max_width: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub max_height: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub every_nth_frame: Option<u32>,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StartScreencastReturnObject {}
+    impl Method for StartScreencast {
+        const NAME: &'static str = "Page.startScreencast";
+        type ReturnObject = StartScreencastReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StopScreencast {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StopScreencastReturnObject {}
+    impl Method for StopScreencast {
+        const NAME: &'static str = "Page.stopScreencast";
+        type ReturnObject = StopScreencastReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ScreencastFrameAck {
+        pub session_id: u32,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ScreencastFrameAckReturnObject {}
+    impl Method for ScreencastFrameAck {
+        const NAME: &'static str = "Page.screencastFrameAck";
+        type ReturnObject = ScreencastFrameAckReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetLayoutMetrics {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetLayoutMetricsReturnObject {
+        pub layout_viewport: super::LayoutViewport,
+        /// Size of the scrollable area. In device pixels on newer versions of Chrome, which
+        /// report it in CSS pixels as `css_content_size` instead.
+        pub content_size: super::Rect,
+        pub css_content_size: Option<super::Rect>,
+    }
+    impl Method for GetLayoutMetrics {
+        const NAME: &'static str = "Page.getLayoutMetrics";
+        type ReturnObject = GetLayoutMetricsReturnObject;
+    }
+
     #[derive(Serialize, Debug)]
     #[serde(rename_all = "camelCase")]
     pub(crate) struct PrintToPdf {
         #[serde(flatten)]
         pub options: Option<PrintToPdfOptions>,
+        /// "ReturnAsBase64" (the default) or "ReturnAsStream"
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub transfer_mode: Option<&'static str>,
     }
     #[derive(Debug, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct PrintToPdfReturnObject {
+        /// Empty when returned as a stream
+        #[serde(default)]
         pub data: String,
+        /// Handle to read the PDF from with IO.read, when returned as a stream
+        pub stream: Option<String>,
     }
     impl Method for PrintToPdf {
         const NAME: &'static str = "Page.printToPDF";
@@ -214,6 +602,20 @@ pub mod methods {
         type ReturnObject = GetFrameTreeReturnObject;
     }
 
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetDocumentContent<'a> {
+        pub frame_id: &'a str,
+        pub html: &'a str,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetDocumentContentReturnObject {}
+    impl<'a> Method for SetDocumentContent<'a> {
+        const NAME: &'static str = "Page.setDocumentContent";
+        type ReturnObject = SetDocumentContentReturnObject;
+    }
+
     #[derive(Serialize, Debug)]
     #[serde(rename_all = "camelCase")]
     pub struct Navigate<'a> {
@@ -242,4 +644,95 @@ pub mod methods {
         type ReturnObject = EnableReturnObject;
     }
 
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Close {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CloseReturnObject {}
+    impl Method for Close {
+        const NAME: &'static str = "Page.close";
+        type ReturnObject = CloseReturnObject;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_pdf_options_in_inches() {
+        let options = PrintToPdfOptionsBuilder::default()
+            .paper_format(PaperFormat::A4)
+            .margins(Length::Px(48.0))
+            .margin_top(Some(Length::Cm(2.54)))
+            .ignore_invalid_page_ranges(Some(true))
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&options).unwrap();
+        assert!((json["paperWidth"].as_f64().unwrap() - 8.27).abs() < 0.01);
+        assert!((json["paperHeight"].as_f64().unwrap() - 11.69).abs() < 0.01);
+        assert_eq!(1.0, json["marginTop"]);
+        assert_eq!(0.5, json["marginLeft"]);
+        assert_eq!(true, json["ignoreInvalidPageRanges"]);
+        assert!(json.get("landscape").is_none());
+
+        assert!(PrintToPdfOptionsBuilder::default()
+            .scale(Some(3.0))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn validates_page_ranges() {
+        for valid in &["1", "1-5, 8, 11-13", "3-", "-3", " 2 - 4 "] {
+            assert_eq!(Ok(()), validate_page_ranges(valid, false), "{}", valid);
+        }
+        for invalid in &["0", "a-3", "1,,2", "-", "1-2-3", "5-3"] {
+            assert!(validate_page_ranges(invalid, false).is_err(), "{}", invalid);
+        }
+        assert_eq!(Ok(()), validate_page_ranges("5-3", true));
+
+        assert!(PrintToPdfOptionsBuilder::default()
+            .page_ranges(Some("2-1".to_string()))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn fills_in_template_placeholders() {
+        assert_eq!(
+            "<div style=\"font-size: 10px; width: 100%; text-align: center;\">\
+             &lt;b&gt; &amp; Page <span class=\"pageNumber\"></span>/\
+             <span class=\"totalPages\"></span> {pages}</div>",
+            header_footer_template("<b> & Page {pageNumber}/{totalPages} {pages}")
+        );
+
+        let options = PrintToPdfOptionsBuilder::default()
+            .footer("{title}")
+            .build()
+            .unwrap();
+        assert_eq!(Some(true), options.display_header_footer);
+        assert_eq!(Some("<span></span>".to_string()), options.header_template);
+
+        let options = PrintToPdfOptionsBuilder::default()
+            .header("{title}")
+            .build()
+            .unwrap();
+        assert_eq!(Some("<span></span>".to_string()), options.footer_template);
+
+        let options = PrintToPdfOptionsBuilder::default()
+            .header("{title}")
+            .footer("{pageNumber}")
+            .build()
+            .unwrap();
+        assert_eq!(
+            Some(header_footer_template("{title}")),
+            options.header_template
+        );
+        assert_eq!(
+            Some(header_footer_template("{pageNumber}")),
+            options.footer_template
+        );
+    }
 }
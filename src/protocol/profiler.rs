@@ -1,8 +1,9 @@
+use std::collections::{BTreeMap, HashMap};
+
 use serde::{Deserialize, Serialize};
 
 // TODO: use these aliases in other parts of the protocol module
 // From experimentation, it seems the protocol's integers are i32s.
-#[allow(dead_code)]
 type JsInt = i32;
 // For when we specifically want to guard against negative numbers.
 type JsUInt = u32;
@@ -40,6 +41,154 @@ pub struct ScriptCoverage {
     pub functions: Vec<FunctionCoverage>,
 }
 
+/// A function on the call stack.
+/// See https://chromedevtools.github.io/devtools-protocol/tot/Runtime#type-CallFrame
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    /// Empty for anonymous functions.
+    pub function_name: String,
+    pub script_id: String,
+    pub url: String,
+    /// Zero-based
+    pub line_number: JsInt,
+    /// Zero-based
+    pub column_number: JsInt,
+}
+
+/// The number of samples taken on a line of a function.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionTickInfo {
+    /// One-based
+    pub line: JsInt,
+    pub ticks: JsUInt,
+}
+
+/// A function in the call tree of a CPU profile.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileNode {
+    pub id: JsUInt,
+    pub call_frame: CallFrame,
+    /// The number of samples in which this was the function being executed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_count: Option<JsUInt>,
+    /// Ids of the functions called from this one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<JsUInt>>,
+    /// Why the function was deoptimized, if it was.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deopt_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_ticks: Option<Vec<PositionTickInfo>>,
+}
+
+/// A sampling CPU profile, as recorded by `Tab::record_cpu_profile`.
+///
+/// Serializing it as JSON gives a `.cpuprofile` file, which Chrome DevTools, speedscope and
+/// VS Code can open. See https://chromedevtools.github.io/devtools-protocol/tot/Profiler#type-Profile
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    /// The call tree, starting with its root.
+    pub nodes: Vec<ProfileNode>,
+    /// Microseconds
+    pub start_time: f64,
+    /// Microseconds
+    pub end_time: f64,
+    /// The id of the node being executed in each sample.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub samples: Option<Vec<JsUInt>>,
+    /// Microseconds between each sample and the previous one (or the start time).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_deltas: Option<Vec<JsInt>>,
+}
+
+impl Profile {
+    /// The profile in the `.cpuprofile` format.
+    pub fn to_cpuprofile(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// The profile in the "collapsed stacks" format understood by flamegraph.pl, inferno and
+    /// speedscope: one line per call stack, with the functions from the root down separated by
+    /// semicolons, followed by the number of samples in which that stack was executing.
+    pub fn to_collapsed_stacks(&self) -> String {
+        let nodes: HashMap<JsUInt, &ProfileNode> =
+            self.nodes.iter().map(|node| (node.id, node)).collect();
+        let mut parents = HashMap::new();
+        for node in &self.nodes {
+            for child in node.children.iter().flatten() {
+                parents.insert(*child, node.id);
+            }
+        }
+
+        // prefer counting the samples themselves, as hit counts of nodes are missing from
+        // profiles recorded by some versions of Chrome
+        let mut hits: BTreeMap<JsUInt, u64> = BTreeMap::new();
+        match self.samples {
+            Some(ref samples) => {
+                for id in samples {
+                    *hits.entry(*id).or_default() += 1;
+                }
+            }
+            None => {
+                for node in &self.nodes {
+                    if let Some(hit_count) = node.hit_count.filter(|&count| count > 0) {
+                        hits.insert(node.id, u64::from(hit_count));
+                    }
+                }
+            }
+        }
+
+        let mut stacks: BTreeMap<String, u64> = BTreeMap::new();
+        for (id, count) in hits {
+            let mut frames = vec![];
+            let mut current = Some(id);
+            while let Some(id) = current {
+                if let Some(node) = nodes.get(&id) {
+                    // the root of every stack is the same, so it's left out
+                    if parents.contains_key(&id) {
+                        frames.push(frame_label(&node.call_frame));
+                    }
+                }
+                current = parents.get(&id).copied();
+            }
+            if frames.is_empty() {
+                continue;
+            }
+            frames.reverse();
+            *stacks.entry(frames.join(";")).or_default() += count;
+        }
+
+        stacks
+            .into_iter()
+            .map(|(stack, count)| format!("{} {}\n", stack, count))
+            .collect()
+    }
+}
+
+fn frame_label(call_frame: &CallFrame) -> String {
+    let name = if call_frame.function_name.is_empty() {
+        "(anonymous)"
+    } else {
+        &call_frame.function_name
+    };
+    let label = if call_frame.url.is_empty() {
+        name.to_string()
+    } else {
+        format!(
+            "{} ({}:{})",
+            name,
+            call_frame.url,
+            call_frame.line_number + 1
+        )
+    };
+    // semicolons separate frames
+    label.replace(';', ":")
+}
+
 pub mod methods {
     use crate::protocol::Method;
     use serde::{Deserialize, Serialize};
@@ -105,4 +254,101 @@ pub mod methods {
         const NAME: &'static str = "Profiler.takePreciseCoverage";
         type ReturnObject = TakePreciseCoverageReturnObject;
     }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetSamplingInterval {
+        /// Microseconds
+        pub interval: super::JsUInt,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetSamplingIntervalReturnObject {}
+    impl Method for SetSamplingInterval {
+        const NAME: &'static str = "Profiler.setSamplingInterval";
+        type ReturnObject = SetSamplingIntervalReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Start {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StartReturnObject {}
+    impl Method for Start {
+        const NAME: &'static str = "Profiler.start";
+        type ReturnObject = StartReturnObject;
+    }
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Stop {}
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StopReturnObject {
+        pub profile: super::Profile,
+    }
+    impl Method for Stop {
+        const NAME: &'static str = "Profiler.stop";
+        type ReturnObject = StopReturnObject;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: JsUInt, function_name: &str, url: &str, children: &[JsUInt]) -> ProfileNode {
+        ProfileNode {
+            id,
+            call_frame: CallFrame {
+                function_name: function_name.to_string(),
+                script_id: "1".to_string(),
+                url: url.to_string(),
+                line_number: 9,
+                column_number: 0,
+            },
+            hit_count: Some(0),
+            children: Some(children.to_vec()),
+            deopt_reason: None,
+            position_ticks: None,
+        }
+    }
+
+    #[test]
+    fn collapses_stacks() {
+        let mut profile = Profile {
+            nodes: vec![
+                node(1, "(root)", "", &[2, 5]),
+                node(2, "", "https://example.com/app.js", &[3, 4]),
+                node(3, "render", "https://example.com/app.js", &[]),
+                node(4, "a;b", "", &[]),
+                node(5, "(idle)", "", &[]),
+            ],
+            start_time: 0.0,
+            end_time: 5000.0,
+            samples: Some(vec![3, 3, 5, 2, 4, 3, 1]),
+            time_deltas: Some(vec![1000; 7]),
+        };
+        assert_eq!(
+            "(anonymous) (https://example.com/app.js:10) 1\n\
+             (anonymous) (https://example.com/app.js:10);a:b 1\n\
+             (anonymous) (https://example.com/app.js:10);render (https://example.com/app.js:10) 3\n\
+             (idle) 1\n",
+            profile.to_collapsed_stacks()
+        );
+
+        // without samples, fall back to the nodes' hit counts
+        profile.samples = None;
+        profile.nodes[4].hit_count = Some(7);
+        assert_eq!("(idle) 7\n", profile.to_collapsed_stacks());
+    }
+
+    #[test]
+    fn round_trips_cpuprofile() {
+        let json = r#"{"nodes":[{"id":1,"callFrame":{"functionName":"(root)","scriptId":"0","url":"","lineNumber":-1,"columnNumber":-1},"hitCount":0,"children":[2]},{"id":2,"callFrame":{"functionName":"(program)","scriptId":"0","url":"","lineNumber":-1,"columnNumber":-1},"hitCount":3}],"startTime":100.0,"endTime":200.0,"samples":[2,2,2],"timeDeltas":[10,20,30]}"#;
+        let profile: Profile = serde_json::from_str(json).unwrap();
+        assert_eq!(2, profile.nodes.len());
+        assert_eq!(json, profile.to_cpuprofile());
+    }
 }
@@ -32,16 +32,49 @@ pub mod methods {
         pub subtype: Option<String>,
         pub description: Option<String>,
         pub class_name: Option<String>,
+        pub object_id: Option<String>,
         pub value: Option<serde_json::Value>,
         pub unserializable_value: Option<String>,
         pub preview: Option<ObjectPreview>,
     }
 
+    /// An argument to a function called with `CallFunctionOn`: either a JSON value or a
+    /// reference to a remote object
+    #[derive(Serialize, Debug, Default, Clone)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CallArgument {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub value: Option<serde_json::Value>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub unserializable_value: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub object_id: Option<String>,
+    }
+
+    #[derive(Serialize, Debug, Default)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Evaluate<'a> {
+        pub expression: &'a str,
+        pub return_by_value: bool,
+        pub await_promise: bool,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EvaluateReturnObject {
+        pub result: RemoteObject,
+    }
+    impl<'a> Method for Evaluate<'a> {
+        const NAME: &'static str = "Runtime.evaluate";
+        type ReturnObject = EvaluateReturnObject;
+    }
+
     #[derive(Serialize, Debug, Default)]
     #[serde(rename_all = "camelCase")]
     pub struct CallFunctionOn<'a> {
         pub object_id: &'a str,
         pub function_declaration: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub arguments: Option<Vec<CallArgument>>,
         pub return_by_value: bool,
         pub generate_preview: bool,
         pub silent: bool,
@@ -0,0 +1,19 @@
+pub mod methods {
+    use crate::protocol::Method;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ClearDataForOrigin<'a> {
+        pub origin: &'a str,
+        /// Comma separated list of storage types to clear, e.g. "cookies,local_storage" or "all"
+        pub storage_types: &'a str,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ClearDataForOriginReturnObject {}
+    impl<'a> Method for ClearDataForOrigin<'a> {
+        const NAME: &'static str = "Storage.clearDataForOrigin";
+        type ReturnObject = ClearDataForOriginReturnObject;
+    }
+}
@@ -95,6 +95,21 @@ pub mod events {
     pub struct TargetDestroyedParams {
         pub target_id: super::TargetId,
     }
+
+    #[derive(Deserialize, Debug, Clone)]
+    pub struct TargetCrashedEvent {
+        pub params: TargetCrashedParams,
+    }
+
+    #[derive(Deserialize, Debug, Clone)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TargetCrashedParams {
+        pub target_id: super::TargetId,
+        /// Termination status type, e.g. "crashed" or "killed"
+        pub status: String,
+        /// Termination error code
+        pub error_code: i32,
+    }
 }
 
 pub mod methods {
@@ -140,6 +155,19 @@ pub mod methods {
         type ReturnObject = CreateBrowserContextReturnObject;
     }
 
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DisposeBrowserContext<'a> {
+        pub browser_context_id: &'a str,
+    }
+    #[derive(Deserialize, Debug, Clone)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DisposeBrowserContextReturnObject {}
+    impl<'a> Method for DisposeBrowserContext<'a> {
+        const NAME: &'static str = "Target.disposeBrowserContext";
+        type ReturnObject = DisposeBrowserContextReturnObject;
+    }
+
     #[derive(Serialize, Debug)]
     #[serde(rename_all = "camelCase")]
     pub struct CreateTarget<'a> {
@@ -165,6 +193,21 @@ pub mod methods {
         type ReturnObject = CreateTargetReturnObject;
     }
 
+    #[derive(Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CloseTarget<'a> {
+        pub target_id: &'a str,
+    }
+    #[derive(Deserialize, Debug, Clone)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CloseTargetReturnObject {
+        pub success: bool,
+    }
+    impl<'a> Method for CloseTarget<'a> {
+        const NAME: &'static str = "Target.closeTarget";
+        type ReturnObject = CloseTargetReturnObject;
+    }
+
     #[derive(Serialize, Debug)]
     #[serde(rename_all = "camelCase")]
     pub struct AttachToTarget<'a> {
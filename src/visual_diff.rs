@@ -0,0 +1,419 @@
+//! Comparing screenshots against a baseline, for visual regression tests.
+//!
+//! Pixels are compared by their perceived color difference, as in
+//! [pixelmatch](https://github.com/mapbox/pixelmatch), so that slight rendering differences
+//! between machines and versions of Chrome can be tolerated with a threshold. Pixels that
+//! only differ because of anti-aliasing are ignored by default.
+//!
+//! ```rust,no_run
+//! # use failure::Error;
+//! # fn main() -> Result<(), Error> {
+//! #
+//! use headless_chrome::{protocol::page::ScreenshotFormat, Browser, LaunchOptionsBuilder};
+//! use headless_chrome::visual_diff::{compare, DiffOptionsBuilder};
+//! let browser = Browser::new(LaunchOptionsBuilder::default().build().unwrap())?;
+//! let tab = browser.wait_for_initial_tab()?;
+//! tab.navigate_to("https://en.wikipedia.org/wiki/WebKit")?.wait_until_navigated()?;
+//!
+//! // the banner changes every day
+//! let banner = tab.wait_for_element("#siteNotice")?.get_box_model()?;
+//! let options = DiffOptionsBuilder::default()
+//!     .masks(vec![banner.margin_viewport()])
+//!     .build()
+//!     .unwrap();
+//!
+//! let baseline = std::fs::read("baseline.png")?;
+//! let screenshot = tab.capture_screenshot(ScreenshotFormat::PNG, None, true)?;
+//! let diff = compare(&baseline, &screenshot, &options)?;
+//! if diff.mismatch_percentage() > 0.1 {
+//!     std::fs::write("diff.png", &diff.image)?;
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use failure::{Error, Fail};
+
+use crate::browser::tab::screenshot::{decode_png, encode_png};
+use crate::protocol::page::Viewport;
+
+/// The largest possible value of `color_delta`
+const MAX_COLOR_DELTA: f64 = 35215.0;
+
+const MISMATCH_COLOR: [u8; 4] = [255, 0, 0, 255];
+const ANTI_ALIASED_COLOR: [u8; 4] = [255, 255, 0, 255];
+
+#[derive(Debug, Fail)]
+#[fail(
+    display = "The images have different sizes: {:?} and {:?}",
+    baseline, actual
+)]
+pub struct DimensionsDiffer {
+    pub baseline: (u32, u32),
+    pub actual: (u32, u32),
+}
+
+#[derive(Debug, Clone, Builder)]
+pub struct DiffOptions {
+    /// How different two pixels' colors may be before they count as a mismatch, from 0 (not
+    /// at all) to 1 (completely). Defaults to 0.1.
+    #[builder(default = "0.1")]
+    pub threshold: f64,
+    /// Don't count pixels that look like they only differ because of anti-aliasing. Defaults
+    /// to true.
+    #[builder(default = "true")]
+    pub ignore_anti_aliasing: bool,
+    /// Regions to leave out of the comparison, e.g. ones with dates or ads in them. These can
+    /// be taken from `Element::get_box_model`.
+    #[builder(default)]
+    pub masks: Vec<Viewport>,
+    /// Number of image pixels per CSS pixel, to convert `masks` with. Should be the device
+    /// scale factor the screenshots were taken at. Defaults to 1.
+    #[builder(default = "1.0")]
+    pub scale: f64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptionsBuilder::default().build().unwrap()
+    }
+}
+
+/// The result of comparing two images
+#[derive(Debug, Clone)]
+pub struct Diff {
+    /// Number of pixels that differ by more than the threshold
+    pub mismatched_pixels: u64,
+    /// Number of pixels compared, i.e. those outside the masks
+    pub compared_pixels: u64,
+    /// A PNG showing mismatched pixels in red and anti-aliasing differences in yellow, on top
+    /// of a faded copy of the baseline
+    pub image: Vec<u8>,
+}
+
+impl Diff {
+    /// The share of compared pixels that mismatched, from 0 to 100
+    // no image has anywhere near 2^52 pixels, so the counts convert to f64 exactly
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mismatch_percentage(&self) -> f64 {
+        if self.compared_pixels == 0 {
+            0.0
+        } else {
+            self.mismatched_pixels as f64 * 100.0 / self.compared_pixels as f64
+        }
+    }
+}
+
+/// Compares two PNGs of the same size pixel by pixel.
+pub fn compare(baseline: &[u8], actual: &[u8], options: &DiffOptions) -> Result<Diff, Error> {
+    let (width, height, baseline) = decode_png(baseline)?;
+    let (actual_width, actual_height, actual) = decode_png(actual)?;
+    if (width, height) != (actual_width, actual_height) {
+        return Err(DimensionsDiffer {
+            baseline: (width, height),
+            actual: (actual_width, actual_height),
+        }
+        .into());
+    }
+
+    let baseline = Image {
+        width,
+        height,
+        pixels: &baseline,
+    };
+    let actual = Image {
+        width,
+        height,
+        pixels: &actual,
+    };
+    let max_delta = MAX_COLOR_DELTA * options.threshold * options.threshold;
+
+    let mut diff_pixels = Vec::with_capacity(baseline.pixels.len());
+    let mut mismatched_pixels = 0;
+    let mut compared_pixels = 0;
+    for y in 0..height {
+        for x in 0..width {
+            let color = if is_masked(x, y, options) {
+                faded(baseline.pixel(x, y), 0.05)
+            } else {
+                compared_pixels += 1;
+                let delta = color_delta(baseline.pixel(x, y), actual.pixel(x, y), false);
+                if delta.abs() <= max_delta {
+                    faded(baseline.pixel(x, y), 0.1)
+                } else if options.ignore_anti_aliasing
+                    && (anti_aliased(&baseline, x, y, &actual)
+                        || anti_aliased(&actual, x, y, &baseline))
+                {
+                    ANTI_ALIASED_COLOR
+                } else {
+                    mismatched_pixels += 1;
+                    MISMATCH_COLOR
+                }
+            };
+            diff_pixels.extend_from_slice(&color);
+        }
+    }
+
+    Ok(Diff {
+        mismatched_pixels,
+        compared_pixels,
+        image: encode_png(width, height, &diff_pixels)?,
+    })
+}
+
+struct Image<'a> {
+    width: u32,
+    height: u32,
+    pixels: &'a [u8],
+}
+
+impl<'a> Image<'a> {
+    fn pixel(&self, x: u32, y: u32) -> &[u8] {
+        let start = ((y * self.width + x) * 4) as usize;
+        &self.pixels[start..start + 4]
+    }
+}
+
+fn is_masked(x: u32, y: u32, options: &DiffOptions) -> bool {
+    let (x, y) = (f64::from(x) + 0.5, f64::from(y) + 0.5);
+    options.masks.iter().any(|mask| {
+        let left = mask.x * options.scale;
+        let top = mask.y * options.scale;
+        x >= left
+            && x < left + mask.width * options.scale
+            && y >= top
+            && y < top + mask.height * options.scale
+    })
+}
+
+/// A pale grayscale version of `pixel`, `opacity` being how much of it shows through on white.
+fn faded(pixel: &[u8], opacity: f64) -> [u8; 4] {
+    let gray = 255.0 + (brightness(pixel) - 255.0) * opacity;
+    // clamped to 0-255 first, so nothing is lost
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let gray = gray.round().clamp(0.0, 255.0) as u8;
+    [gray, gray, gray, 255]
+}
+
+fn blend_with_white(channel: u8, alpha: u8) -> f64 {
+    255.0 + (f64::from(channel) - 255.0) * f64::from(alpha) / 255.0
+}
+
+fn brightness(pixel: &[u8]) -> f64 {
+    let r = blend_with_white(pixel[0], pixel[3]);
+    let g = blend_with_white(pixel[1], pixel[3]);
+    let b = blend_with_white(pixel[2], pixel[3]);
+    r * 0.298_895_31 + g * 0.586_622_47 + b * 0.114_482_23
+}
+
+/// The perceived difference between two RGBA pixels in YIQ color space, see "Measuring
+/// perceived color difference using YIQ NTSC transmission color space in mobile applications"
+/// by Y. Kotsarenko and F. Ramos. With `brightness_only`, it's the (signed) difference in
+/// brightness instead.
+fn color_delta(a: &[u8], b: &[u8], brightness_only: bool) -> f64 {
+    if a == b {
+        return 0.0;
+    }
+    let dy = brightness(a) - brightness(b);
+    if brightness_only {
+        return dy;
+    }
+
+    let (r1, g1, b1) = (
+        blend_with_white(a[0], a[3]),
+        blend_with_white(a[1], a[3]),
+        blend_with_white(a[2], a[3]),
+    );
+    let (r2, g2, b2) = (
+        blend_with_white(b[0], b[3]),
+        blend_with_white(b[1], b[3]),
+        blend_with_white(b[2], b[3]),
+    );
+    let di = (r1 - r2) * 0.595_977_99 - (g1 - g2) * 0.274_176_10 - (b1 - b2) * 0.321_801_89;
+    let dq = (r1 - r2) * 0.211_470_17 - (g1 - g2) * 0.522_617_11 + (b1 - b2) * 0.311_146_94;
+    0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq
+}
+
+/// The 3x3 (or smaller, at the edges) neighbourhood of a pixel
+fn neighbours(image: &Image, x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> {
+    let (x0, x1) = (x.saturating_sub(1), (x + 1).min(image.width - 1));
+    let (y0, y1) = (y.saturating_sub(1), (y + 1).min(image.height - 1));
+    (x0..=x1)
+        .flat_map(move |nx| (y0..=y1).map(move |ny| (nx, ny)))
+        .filter(move |&neighbour| neighbour != (x, y))
+}
+
+fn on_edge(image: &Image, x: u32, y: u32) -> bool {
+    x == 0 || y == 0 || x == image.width - 1 || y == image.height - 1
+}
+
+/// Whether the pixel at (x, y) in `image` looks like part of an anti-aliased edge: it has
+/// both darker and brighter neighbours, and one of those extremes is part of a solid area in
+/// both images. Based on "Anti-aliased Pixel and Intensity Slope Detector" by V. Vysniauskas.
+fn anti_aliased(image: &Image, x: u32, y: u32, other: &Image) -> bool {
+    let mut identical = u32::from(on_edge(image, x, y));
+    // the neighbours that are the most brighter and the most darker than this pixel
+    let mut min = (0.0, None);
+    let mut max = (0.0, None);
+
+    for (nx, ny) in neighbours(image, x, y) {
+        let delta = color_delta(image.pixel(x, y), image.pixel(nx, ny), true);
+        if delta == 0.0 {
+            identical += 1;
+            // with this many identical neighbours, it's not an edge
+            if identical > 2 {
+                return false;
+            }
+        } else if delta < min.0 {
+            min = (delta, Some((nx, ny)));
+        } else if delta > max.0 {
+            max = (delta, Some((nx, ny)));
+        }
+    }
+
+    if let (Some(min), Some(max)) = (min.1, max.1) {
+        (has_many_siblings(image, min) && has_many_siblings(other, min))
+            || (has_many_siblings(image, max) && has_many_siblings(other, max))
+    } else {
+        // anti-aliasing always has a gradient on either side
+        false
+    }
+}
+
+/// Whether the pixel has at least three neighbours of the exact same color
+fn has_many_siblings(image: &Image, (x, y): (u32, u32)) -> bool {
+    let mut identical = u32::from(on_edge(image, x, y));
+    for (nx, ny) in neighbours(image, x, y) {
+        if image.pixel(x, y) == image.pixel(nx, ny) {
+            identical += 1;
+            if identical > 2 {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+    /// A PNG of `width`x`height` white pixels, with the given pixels colored in
+    fn png(width: u32, height: u32, colored: &[((u32, u32), [u8; 4])]) -> Vec<u8> {
+        let mut pixels: Vec<u8> = WHITE
+            .iter()
+            .cycle()
+            .take((width * height * 4) as usize)
+            .cloned()
+            .collect();
+        for ((x, y), color) in colored {
+            let start = ((y * width + x) * 4) as usize;
+            pixels[start..start + 4].copy_from_slice(color);
+        }
+        encode_png(width, height, &pixels).unwrap()
+    }
+
+    #[test]
+    fn identical_images_match() {
+        let image = png(5, 5, &[((2, 2), BLACK)]);
+        let diff = compare(&image, &image, &DiffOptions::default()).unwrap();
+        assert_eq!(0, diff.mismatched_pixels);
+        assert_eq!(25, diff.compared_pixels);
+        assert_eq!(0.0, diff.mismatch_percentage());
+    }
+
+    #[test]
+    fn counts_and_highlights_mismatches() {
+        let baseline = png(5, 4, &[]);
+        let actual = png(5, 4, &[((1, 1), BLACK), ((3, 2), BLACK)]);
+        let diff = compare(&baseline, &actual, &DiffOptions::default()).unwrap();
+        assert_eq!(2, diff.mismatched_pixels);
+        assert_eq!(10.0, diff.mismatch_percentage());
+
+        let (width, height, pixels) = decode_png(&diff.image).unwrap();
+        assert_eq!((5, 4), (width, height));
+        assert_eq!(&MISMATCH_COLOR, &pixels[(5 + 1) * 4..(5 + 1) * 4 + 4]);
+        assert_eq!(&WHITE, &pixels[0..4]);
+    }
+
+    #[test]
+    fn tolerates_differences_below_threshold() {
+        let baseline = png(3, 3, &[((1, 1), [100, 100, 100, 255])]);
+        let actual = png(3, 3, &[((1, 1), [104, 100, 100, 255])]);
+        let diff = compare(&baseline, &actual, &DiffOptions::default()).unwrap();
+        assert_eq!(0, diff.mismatched_pixels);
+
+        let strict = DiffOptionsBuilder::default()
+            .threshold(0.0)
+            .build()
+            .unwrap();
+        assert_eq!(
+            1,
+            compare(&baseline, &actual, &strict)
+                .unwrap()
+                .mismatched_pixels
+        );
+    }
+
+    #[test]
+    fn ignores_masked_regions() {
+        let baseline = png(6, 6, &[]);
+        let actual = png(6, 6, &[((4, 4), BLACK), ((0, 0), BLACK)]);
+        let options = DiffOptionsBuilder::default()
+            .masks(vec![Viewport {
+                x: 2.0,
+                y: 2.0,
+                width: 1.0,
+                height: 1.0,
+                scale: 1.0,
+            }])
+            .scale(2.0)
+            .build()
+            .unwrap();
+        let diff = compare(&baseline, &actual, &options).unwrap();
+        assert_eq!(1, diff.mismatched_pixels);
+        assert_eq!(32, diff.compared_pixels);
+    }
+
+    #[test]
+    fn ignores_anti_aliasing() {
+        // the edge of a black square, which is slightly blurred in `actual`
+        let gray = [128, 128, 128, 255];
+        let square: Vec<_> = (0..3)
+            .flat_map(|x| (0..7).map(move |y| ((x, y), BLACK)))
+            .collect();
+        let baseline = png(7, 7, &square);
+        let mut blurred = square.clone();
+        blurred.push(((3, 3), gray));
+        let actual = png(7, 7, &blurred);
+
+        let diff = compare(&baseline, &actual, &DiffOptions::default()).unwrap();
+        assert_eq!(0, diff.mismatched_pixels);
+        let (_, _, pixels) = decode_png(&diff.image).unwrap();
+        assert_eq!(
+            &ANTI_ALIASED_COLOR,
+            &pixels[(7 * 3 + 3) * 4..(7 * 3 + 3) * 4 + 4]
+        );
+
+        let options = DiffOptionsBuilder::default()
+            .ignore_anti_aliasing(false)
+            .build()
+            .unwrap();
+        assert_eq!(
+            1,
+            compare(&baseline, &actual, &options)
+                .unwrap()
+                .mismatched_pixels
+        );
+    }
+
+    #[test]
+    fn different_sizes_dont_compare() {
+        let error = compare(&png(2, 2, &[]), &png(2, 3, &[]), &DiffOptions::default()).unwrap_err();
+        assert!(error.downcast_ref::<DimensionsDiffer>().is_some());
+    }
+}
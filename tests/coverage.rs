@@ -5,7 +5,7 @@ use failure::Error;
 use headless_chrome::{Browser, LaunchOptionsBuilder};
 
 use headless_chrome::browser::default_executable;
-use headless_chrome::browser::tab::Tab;
+use headless_chrome::browser::tab::{coverage, Tab};
 use server::Server;
 use std::sync::Arc;
 
@@ -123,3 +123,131 @@ fn returns_actual_coverage() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn records_cpu_profile() -> Result<(), Error> {
+    logging::enable_logging();
+    let server = server_with_html_and_js();
+    let browser = Browser::new(
+        LaunchOptionsBuilder::default()
+            .headless(true)
+            .path(Some(default_executable().unwrap()))
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    let tab: Arc<Tab> = browser.wait_for_initial_tab()?;
+
+    let url = format!("http://127.0.0.1:{}", server.port());
+    let profile = tab.record_cpu_profile(|| {
+        tab.navigate_to(&url)?.wait_until_navigated()?;
+        tab.wait_for_element("#incrementor")?.click()?;
+        Ok(())
+    })?;
+
+    assert_eq!("(root)", profile.nodes[0].call_frame.function_name);
+    assert!(profile.end_time > profile.start_time);
+    assert!(profile.to_cpuprofile().starts_with("{\"nodes\":["));
+
+    // every line is a stack followed by its number of samples
+    let stacks = profile.to_collapsed_stacks();
+    assert!(!stacks.is_empty());
+    for line in stacks.lines() {
+        let (stack, count) = line.split_at(line.rfind(' ').unwrap());
+        assert!(!stack.is_empty());
+        assert!(count.trim().parse::<u64>()? > 0);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn reports_coverage_as_lcov_and_istanbul() -> Result<(), Error> {
+    logging::enable_logging();
+    let server = server_with_html_and_js();
+    let browser = Browser::new(
+        LaunchOptionsBuilder::default()
+            .headless(true)
+            .path(Some(default_executable().unwrap()))
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    let tab: Arc<Tab> = browser.wait_for_initial_tab()?;
+
+    tab.enable_profiler()?;
+    tab.start_js_coverage()?;
+    let url = format!("http://127.0.0.1:{}", server.port());
+    tab.navigate_to(&url)?;
+    tab.wait_until_navigated()?;
+
+    let scripts = tab.take_js_coverage_report(Some("*/coverage_fixture1.js"))?;
+    assert_eq!(1, scripts.len());
+    let script = &scripts[0];
+
+    // button.onclick hasn't been called, so the line in its body hasn't been executed
+    let onclick = script
+        .functions
+        .iter()
+        .find(|function| function.name == "button.onclick")
+        .unwrap();
+    assert_eq!(0, onclick.count);
+    assert_eq!(5, onclick.start.line);
+    let line_counts: Vec<(u32, u32)> = script
+        .lines
+        .iter()
+        .map(|line| (line.line, line.count))
+        .collect();
+    assert_eq!(
+        vec![(1, 1), (2, 1), (3, 1), (4, 1), (5, 1), (6, 0), (7, 1)],
+        line_counts
+    );
+
+    let lcov = coverage::to_lcov(&scripts);
+    assert!(lcov.contains(&format!("SF:{}/coverage_fixture1.js\n", url)));
+    assert!(lcov.contains("DA:6,0\n"));
+    assert!(lcov.contains("LF:7\nLH:6\n"));
+
+    let istanbul = coverage::to_istanbul(&scripts);
+    let file = &istanbul[&format!("{}/coverage_fixture1.js", url)];
+    assert_eq!(0, file["s"]["5"]);
+
+    Ok(())
+}
+
+#[test]
+fn reports_css_coverage() -> Result<(), Error> {
+    logging::enable_logging();
+    let server = Server::with_dumb_html(
+        "<html><head><style>\n\
+         p { color: red; }\n\
+         .missing { color: blue; }\n\
+         </style></head><body><p>Styled</p></body></html>",
+    );
+    let browser = Browser::new(
+        LaunchOptionsBuilder::default()
+            .headless(true)
+            .path(Some(default_executable().unwrap()))
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    let tab: Arc<Tab> = browser.wait_for_initial_tab()?;
+
+    tab.start_css_coverage()?;
+    tab.navigate_to(&server.url())?;
+    tab.wait_until_navigated()?;
+    let style_sheets = tab.stop_css_coverage()?;
+
+    assert_eq!(1, style_sheets.len());
+    let style_sheet = &style_sheets[0];
+    assert!(style_sheet.is_inline);
+    assert_eq!("p { color: red; }", style_sheet.used_text());
+    assert_eq!(1, style_sheet.unused.len());
+    assert_eq!(
+        ".missing { color: blue; }",
+        &style_sheet.text[style_sheet.unused[0].clone()]
+    );
+
+    Ok(())
+}
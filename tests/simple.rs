@@ -1,19 +1,33 @@
 #![allow(unused_variables)]
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use base64;
 use log::*;
 use rand::prelude::*;
 
-use headless_chrome::browser::tab::RequestInterceptionDecision;
+use headless_chrome::browser::tab::element::{
+    Direction, Element, ElementNotActionable, OptionNotFound,
+};
+use headless_chrome::browser::tab::keyboard::KeyboardLayout;
+use headless_chrome::browser::tab::point::Point;
+use headless_chrome::browser::tab::screencast::{ScreencastFormat, ScreencastOptionsBuilder};
+use headless_chrome::browser::tab::{RequestInterceptionDecision, TabClosed, TabCrashed};
+use headless_chrome::browser::{BrowserCrashed, BrowserEvent};
+use headless_chrome::protocol::emulation::methods::SetDeviceMetricsOverride;
 use headless_chrome::protocol::network::methods::RequestPattern;
+use headless_chrome::util::Wait;
 use headless_chrome::{
-    browser::default_executable, browser::tab::Tab, protocol::page::ScreenshotFormat, Browser,
-    LaunchOptionsBuilder,
+    browser::default_executable,
+    browser::tab::Tab,
+    protocol::page::{
+        Length, PaperFormat, PrintToPdfOptionsBuilder, ScreenshotFormat, ScreenshotOptionsBuilder,
+    },
+    Browser, LaunchOptionsBuilder,
 };
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 mod logging;
 mod server;
@@ -88,6 +102,295 @@ fn form_interaction() -> Result<(), failure::Error> {
     Ok(())
 }
 
+#[test]
+fn keyboard_modifiers_and_chords() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("form.html"));
+    let keyboard = tab.keyboard();
+    tab.wait_for_element("input#target")?.type_into("moon")?;
+
+    // replace what we typed, with a shifted letter at the start
+    keyboard.press_chord("Control+A")?;
+    keyboard.down("Shift")?.press("m")?.up("Shift")?;
+    assert_eq!(0, keyboard.modifiers());
+    tab.type_str("othership")?;
+
+    tab.wait_for_element("button")?.click()?;
+    let d = tab.wait_for_element("div#protocol")?.get_description()?;
+    assert!(d
+        .find(|n| n.node_value == "Missiles launched against Mothership")
+        .is_some());
+    Ok(())
+}
+
+#[test]
+fn type_characters_missing_from_keyboard_layout() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("form.html"));
+    tab.wait_for_element("input#target")?
+        .type_into("Grüße aus 東京 🚀")?;
+    tab.keyboard().compose("!")?;
+
+    tab.wait_for_element("button")?.click()?;
+    let d = tab.wait_for_element("div#protocol")?.get_description()?;
+    assert!(d
+        .find(|n| n.node_value == "Missiles launched against Grüße aus 東京 🚀!")
+        .is_some());
+    Ok(())
+}
+
+#[test]
+fn type_with_german_keyboard_layout() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("form.html"));
+    tab.set_keyboard_layout(KeyboardLayout::german());
+    tab.wait_for_element("input#target")?
+        .type_into("Größe @ 7/8")?;
+
+    tab.wait_for_element("button")?.click()?;
+    let d = tab.wait_for_element("div#protocol")?.get_description()?;
+    assert!(d
+        .find(|n| n.node_value == "Missiles launched against Größe @ 7/8")
+        .is_some());
+    Ok(())
+}
+
+#[test]
+fn mouse_buttons_clicks_and_drags() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("mouse.html"));
+    let logged = |message: &str| -> Result<bool, failure::Error> {
+        let d = tab.wait_for_element("div#log")?.get_description()?;
+        Ok(d.find(|n| n.node_value == message).is_some())
+    };
+
+    let clickable = tab.wait_for_element("#clickable")?;
+    clickable.double_click()?.right_click()?;
+    assert!(logged("dblclick")?);
+    assert!(logged("contextmenu")?);
+
+    tab.keyboard().down("Shift")?;
+    clickable.click()?;
+    tab.keyboard().up("Shift")?;
+    assert!(logged("shift click")?);
+
+    tab.wait_for_element("#hoverable")?.hover()?;
+    assert!(logged("mouseover")?);
+
+    tab.wait_for_element("#source")?
+        .drag_to(&tab.wait_for_element("#target")?)?;
+    assert!(logged("dropped")?);
+
+    tab.mouse().wheel(0.0, 100.0)?;
+    Wait::with_timeout(Duration::from_secs(5)).until(|| logged("wheel 100").ok().filter(|l| *l))?;
+    Ok(())
+}
+
+#[test]
+fn tap_and_swipe() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("touch.html"));
+    let logged = |message: &str| -> Result<bool, failure::Error> {
+        let d = tab.wait_for_element("div#log")?.get_description()?;
+        Ok(d.find(|n| n.node_value == message).is_some())
+    };
+
+    tab.wait_for_element("#tappable")?.tap()?;
+    assert!(logged("touchstart")?);
+    assert!(logged("click")?);
+
+    let carousel = tab.wait_for_element("#carousel")?.get_box_model()?.content;
+    let middle_y = (carousel.top_left.y + carousel.bottom_left.y) / 2.0;
+    tab.swipe(
+        Point {
+            x: carousel.most_right() - 10.0,
+            y: middle_y,
+        },
+        Point {
+            x: carousel.most_left() + 10.0,
+            y: middle_y,
+        },
+    )?;
+    assert!(logged("swiped left")?);
+    Ok(())
+}
+
+#[test]
+fn click_waits_until_element_is_actionable() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("actionability.html"));
+    let logged = |message: &str| -> Result<bool, failure::Error> {
+        let d = tab.wait_for_element("div#log")?.get_description()?;
+        Ok(d.find(|n| n.node_value == message).is_some())
+    };
+
+    tab.wait_for_element("#disabled")?.click()?;
+    tab.wait_for_element("#covered")?.click()?;
+    tab.wait_for_element("#sliding")?.click()?;
+    tab.wait_for_element("#far-away")?.click()?;
+    assert!(logged("clicked disabled")?);
+    assert!(logged("clicked covered")?);
+    assert!(logged("clicked sliding")?);
+    assert!(logged("clicked far away")?);
+
+    let error = tab.wait_for_element("#hidden")?.click().unwrap_err();
+    let not_actionable = error.downcast_ref::<ElementNotActionable>().unwrap();
+    assert_eq!("it isn't visible", not_actionable.reason);
+    Ok(())
+}
+
+#[test]
+fn read_element_state() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("form.html"));
+
+    let input = tab.wait_for_element("input#target")?;
+    input.type_into("mothership")?;
+    assert_eq!("mothership", input.get_value()?);
+    assert_eq!(Some("text".to_string()), input.get_attribute("type")?);
+    assert_eq!(None, input.get_attribute("placeholder")?);
+    assert_eq!("INPUT", input.get_property::<String>("tagName")?);
+    assert_eq!(
+        None,
+        input.get_property::<Option<String>>("noSuchProperty")?
+    );
+    assert!(input.is_visible()?);
+    assert!(input.is_enabled()?);
+
+    let checkbox = tab.wait_for_element("input#sneakattack")?;
+    assert!(!checkbox.is_checked()?);
+    checkbox.click()?;
+    assert!(checkbox.is_checked()?);
+
+    let button = tab.wait_for_element("button")?;
+    assert_eq!("Launch the missiles!", button.get_inner_text()?);
+    assert_eq!("Launch the missiles!", button.get_inner_html()?);
+    assert!(button.get_outer_html()?.starts_with("<button"));
+    assert_eq!("inline-block", button.get_computed_style("display")?);
+    Ok(())
+}
+
+#[test]
+fn fill_in_forms() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("fill_form.html"));
+
+    let mut values = HashMap::new();
+    values.insert("#name", "Laika");
+    values.insert("#notes", "Didn't come back");
+    values.insert("#species", "Dog");
+    values.insert("#good", "true");
+    values.insert("#returned", "false");
+    values.insert("#log", "Sputnik 2");
+    tab.fill_form(values)?;
+
+    let value_of = |selector| tab.wait_for_element(selector)?.get_value();
+    assert_eq!("Laika", value_of("#name")?);
+    assert_eq!("Didn't come back", value_of("#notes")?);
+    assert_eq!("dog", value_of("#species")?);
+    assert!(tab.wait_for_element("#good")?.is_checked()?);
+    assert!(!tab.wait_for_element("#returned")?.is_checked()?);
+    assert_eq!("Sputnik 2", tab.wait_for_element("#log")?.get_inner_text()?);
+    assert_eq!(
+        "species changed;",
+        tab.wait_for_element("#events")?.get_inner_text()?
+    );
+
+    let crew = tab.wait_for_element("#crew")?;
+    let selected = || {
+        crew.call_js_fn(
+            "function() { return Array.from(this.selectedOptions).map(o => o.value).join(); }",
+            false,
+        )
+        .map(|result| result.value)
+    };
+    crew.select_options(&["Gagarin", "Leonov"])?;
+    assert_eq!(Some("Gagarin,Leonov".into()), selected()?);
+
+    let error = crew
+        .select_options(&["Tereshkova", "Armstrong"])
+        .unwrap_err();
+    assert!(error.downcast_ref::<OptionNotFound>().is_some());
+    assert_eq!(Some("Gagarin,Leonov".into()), selected()?);
+
+    let checkbox = tab.wait_for_element("#returned")?;
+    checkbox.check()?.check()?;
+    assert!(checkbox.is_checked()?);
+    checkbox.uncheck()?;
+    assert!(!checkbox.is_checked()?);
+    Ok(())
+}
+
+#[test]
+fn navigate_relative_to_elements() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("cards.html"));
+
+    let card = tab.wait_for_element("#voskhod")?;
+    assert_eq!("Book", card.find_element("button.book")?.get_inner_text()?);
+    assert_eq!(2, card.find_elements("button")?.len());
+    assert!(card.find_element("h3").is_err());
+    assert!(tab
+        .wait_for_element("#vostok")?
+        .find_element("button.book")
+        .is_err());
+
+    let children = tab.wait_for_element("#vostok")?.children()?;
+    assert_eq!(2, children.len());
+    assert_eq!("Vostok 1", children[0].get_inner_text()?);
+
+    let next = children[0].next_sibling()?.unwrap();
+    assert_eq!("Details", next.get_inner_text()?);
+    assert!(next.next_sibling()?.is_none());
+
+    let parent = next.parent()?.unwrap();
+    assert_eq!(Some("vostok".to_string()), parent.get_attribute("id")?);
+    assert!(tab.wait_for_element("html")?.parent()?.is_none());
+
+    let list = next.closest("ul")?.unwrap();
+    assert_eq!(Some("cards".to_string()), list.get_attribute("id")?);
+    assert!(next.closest("button")?.is_some());
+    assert!(next.closest("table")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn find_elements_by_layout() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("layout.html"));
+    tab.wait_for_element("#email")?;
+
+    let id_of = |element: &Element| element.get_attribute("id").unwrap().unwrap();
+
+    let email_label = tab
+        .find_elements("label")?
+        .into_iter()
+        .find(|label| label.get_inner_text().unwrap() == "Email")
+        .unwrap();
+    let right_of_label = tab.find_elements_near("input", &email_label, Direction::RightOf, None)?;
+    let ids: Vec<_> = right_of_label.iter().map(id_of).collect();
+    assert_eq!(vec!["email", "name", "far-away"], ids);
+
+    let nearby = tab.find_elements_near("input", &email_label, Direction::RightOf, Some(100.0))?;
+    let ids: Vec<_> = nearby.iter().map(id_of).collect();
+    assert_eq!(vec!["email", "name"], ids);
+
+    let name = tab.wait_for_element("#name")?;
+    let below_name = tab.find_elements_near("input", &name, Direction::Below, Some(50.0))?;
+    assert_eq!(
+        vec!["email"],
+        below_name.iter().map(id_of).collect::<Vec<_>>()
+    );
+    assert!(tab
+        .find_elements_near("input", &name, Direction::Above, None)
+        .is_err());
+
+    let in_order = tab.find_elements_in_reading_order("input")?;
+    let ids: Vec<_> = in_order.iter().map(id_of).collect();
+    assert_eq!(vec!["name", "email", "far-away", "hidden"], ids);
+    Ok(())
+}
+
 fn decode_png(i: &[u8]) -> Result<Vec<u8>, failure::Error> {
     let decoder = png::Decoder::new(&i[..]);
     let (info, mut reader) = decoder.read_info()?;
@@ -159,6 +462,151 @@ fn capture_screenshot_jpeg() -> Result<(), failure::Error> {
     Ok(())
 }
 
+#[test]
+fn capture_screenshot_full_page() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("tall.html"));
+    tab.wait_for_element("#bottom")?;
+    let viewport_png = tab.capture_screenshot(ScreenshotFormat::PNG, None, true)?;
+    let viewport = png::Decoder::new(&viewport_png[..]).read_info()?.0;
+
+    let options = ScreenshotOptionsBuilder::default()
+        .full_page(true)
+        .build()
+        .unwrap();
+    let png_data = tab.capture_screenshot_with_options(options)?;
+    let (info, mut reader) = png::Decoder::new(&png_data[..]).read_info()?;
+    assert_eq!(3100, info.height);
+    let mut buf = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buf)?;
+    let bytes_per_pixel = info.line_size / info.width as usize;
+    assert!(sum_of_errors(&buf[0..3], &[0x11, 0x22, 0x33]) < 5);
+    assert!(
+        sum_of_errors(
+            &buf[buf.len() - bytes_per_pixel..][..3],
+            &[0x33, 0x22, 0x11]
+        ) < 5
+    );
+
+    // the viewport is back to its original size
+    let after_png = tab.capture_screenshot(ScreenshotFormat::PNG, None, true)?;
+    let after = png::Decoder::new(&after_png[..]).read_info()?.0;
+    assert_eq!(
+        (viewport.width, viewport.height),
+        (after.width, after.height)
+    );
+
+    // and so is an emulated device's
+    tab.set_device_metrics_override(SetDeviceMetricsOverride {
+        width: 400,
+        height: 300,
+        device_scale_factor: 1.0,
+        mobile: false,
+    })?;
+    let options = ScreenshotOptionsBuilder::default()
+        .full_page(true)
+        .build()
+        .unwrap();
+    tab.capture_screenshot_with_options(options)?;
+    let emulated_png = tab.capture_screenshot(ScreenshotFormat::PNG, None, true)?;
+    let emulated = png::Decoder::new(&emulated_png[..]).read_info()?.0;
+    assert_eq!((400, 300), (emulated.width, emulated.height));
+    tab.clear_device_metrics_override()?;
+    Ok(())
+}
+
+#[test]
+fn capture_transparent_scaled_and_webp_screenshots() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("transparent.html"));
+    let badge = tab.wait_for_element("#badge")?.get_box_model()?;
+
+    let options = ScreenshotOptionsBuilder::default()
+        .clip(Some(badge.margin_viewport()))
+        .device_scale_factor(Some(2.0))
+        .omit_background(true)
+        .build()
+        .unwrap();
+    let png_data = tab.capture_screenshot_with_options(options)?;
+    let (info, mut reader) = png::Decoder::new(&png_data[..]).read_info()?;
+    assert_eq!((200, 120), (info.width, info.height));
+    assert_eq!(png::ColorType::RGBA, info.color_type);
+    let mut buf = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buf)?;
+    // the margin is transparent, the middle of the badge isn't
+    assert_eq!(0, buf[3]);
+    let middle = ((60 * 200) + 100) * 4;
+    assert!(sum_of_errors(&buf[middle..middle + 4], &[0x11, 0x22, 0x33, 0xff]) < 5);
+
+    // the background is back to normal afterwards
+    let png_data = tab.capture_screenshot(ScreenshotFormat::PNG, None, true)?;
+    let buf = decode_png(&png_data[..])?;
+    assert!(sum_of_errors(&buf[0..4], &[0xff, 0xff, 0xff, 0xff]) < 5);
+
+    let options = ScreenshotOptionsBuilder::default()
+        .format(ScreenshotFormat::WEBP(Some(90)))
+        .build()
+        .unwrap();
+    let webp_data = tab.capture_screenshot_with_options(options)?;
+    assert_eq!(b"RIFF", &webp_data[0..4]);
+    assert_eq!(b"WEBP", &webp_data[8..12]);
+
+    // scaled screenshots leave an emulated device's viewport as it was
+    tab.set_device_metrics_override(SetDeviceMetricsOverride {
+        width: 400,
+        height: 300,
+        device_scale_factor: 1.0,
+        mobile: false,
+    })?;
+    let options = ScreenshotOptionsBuilder::default()
+        .device_scale_factor(Some(2.0))
+        .build()
+        .unwrap();
+    let png_data = tab.capture_screenshot_with_options(options)?;
+    let scaled = png::Decoder::new(&png_data[..]).read_info()?.0;
+    assert_eq!((800, 600), (scaled.width, scaled.height));
+    let png_data = tab.capture_screenshot(ScreenshotFormat::PNG, None, true)?;
+    let after = png::Decoder::new(&png_data[..]).read_info()?.0;
+    assert_eq!((400, 300), (after.width, after.height));
+    tab.clear_device_metrics_override()?;
+    Ok(())
+}
+
+#[test]
+fn record_screencast() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (server, browser, tab) = dumb_server(include_str!("simple.html"));
+    tab.wait_for_element("div#foobar")?;
+
+    let options = ScreencastOptionsBuilder::default()
+        .format(ScreencastFormat::PNG)
+        .max_width(Some(400))
+        .build()
+        .unwrap();
+    let frames = tab.start_screencast(options)?;
+    // keep the page changing so that frames are sent
+    tab.wait_for_element("div#foobar")?.call_js_fn(
+        "function() { let n = 0; setInterval(() => { this.innerText = n++; }, 50); }",
+        false,
+    )?;
+    let first = frames.recv_timeout(Duration::from_secs(10))?;
+    assert!(first.data.starts_with(b"\x89PNG"));
+    assert!(first.timestamp().is_some());
+    assert!(first.metadata.device_width > 0.0);
+    tab.stop_screencast()?;
+
+    let dir = tempfile::tempdir()?;
+    let options = ScreencastOptionsBuilder::default().build().unwrap();
+    let writer = tab.start_screencast_to_dir(options, dir.path())?;
+    sleep(Duration::from_millis(500));
+    tab.stop_screencast()?;
+    let paths = writer.join().unwrap()?;
+    assert!(!paths.is_empty());
+    assert!(paths[0].to_str().unwrap().ends_with(".jpg"));
+    assert!(std::fs::read(&paths[0])?.starts_with(&[0xff, 0xd8]));
+    Ok(())
+}
+
 #[test]
 fn test_print_file_to_pdf() -> Result<(), failure::Error> {
     logging::enable_logging();
@@ -169,6 +617,72 @@ fn test_print_file_to_pdf() -> Result<(), failure::Error> {
     Ok(())
 }
 
+#[test]
+fn stream_pdf_into_writer() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("./pdfassets/index.html"));
+    tab.wait_until_navigated()?;
+
+    let mut pdf = vec![];
+    let mut progress = vec![];
+    let size = tab.print_to_pdf_stream(None, &mut pdf, |written| progress.push(written))?;
+    assert!(pdf.starts_with(b"%PDF"));
+    assert_eq!(size, pdf.len() as u64);
+    assert_eq!(Some(&size), progress.last());
+    assert!(progress.windows(2).all(|pair| pair[0] < pair[1]));
+    Ok(())
+}
+
+#[test]
+fn print_pdf_with_options() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("./pdfassets/index.html"));
+    tab.wait_until_navigated()?;
+
+    let options = PrintToPdfOptionsBuilder::default()
+        .paper_format(PaperFormat::A5)
+        .landscape(Some(true))
+        .margins(Length::Cm(1.5))
+        .page_ranges(Some("1".to_string()))
+        .header("{title}")
+        .footer("Page {pageNumber} of {totalPages}")
+        .build()
+        .unwrap();
+    let pdf = tab.print_to_pdf(Some(options))?;
+    assert!(pdf.starts_with(b"%PDF"));
+
+    let out_of_range = PrintToPdfOptionsBuilder::default()
+        .page_ranges(Some("1000".to_string()))
+        .build()
+        .unwrap();
+    assert!(tab.print_to_pdf(Some(out_of_range)).is_err());
+    Ok(())
+}
+
+#[test]
+fn set_content_and_print_html_to_pdf() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (server, browser, tab) = dumb_server(include_str!("simple.html"));
+    tab.wait_until_navigated()?;
+
+    tab.set_content("<p id=\"replaced\">Set directly</p>")?;
+    let paragraph = tab.wait_for_element("p#replaced")?;
+    assert_eq!("Set directly", paragraph.get_inner_text()?);
+    assert!(tab.get_url().starts_with(&server.url()));
+
+    let tabs_before = browser.get_tabs().lock().unwrap().len();
+    let pdf = browser.html_to_pdf(
+        "<html><head></head><body><img src=\"image.png\"><h1>Hello</h1></body></html>",
+        Some(&server.url()),
+        None,
+    )?;
+    assert!(pdf.starts_with(b"%PDF"));
+    // the tab used for printing gets closed again
+    Wait::with_timeout(Duration::from_secs(5))
+        .until(|| Some(browser.get_tabs().lock().unwrap().len()).filter(|&n| n == tabs_before))?;
+    Ok(())
+}
+
 #[test]
 fn get_box_model() -> Result<(), failure::Error> {
     logging::enable_logging();
@@ -399,6 +913,34 @@ fn incognito_contexts() -> Result<(), failure::Error> {
     Ok(())
 }
 
+#[test]
+fn close_tabs_and_dispose_contexts() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, _tab) = dumb_server(include_str!("simple.html"));
+    let tab_is_listed = |tab: &Arc<Tab>| {
+        browser
+            .get_tabs()
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|other| other.get_target_id() == tab.get_target_id())
+    };
+
+    let new_tab = browser.new_tab()?;
+    new_tab.close(false)?;
+    assert!(new_tab.is_closed());
+    assert!(!tab_is_listed(&new_tab));
+    let error = new_tab.get_target_info().unwrap_err();
+    assert!(error.downcast_ref::<TabClosed>().is_some());
+
+    let context = browser.new_context()?;
+    let incognito_tab = context.new_tab()?;
+    context.dispose()?;
+    assert!(incognito_tab.is_closed());
+    assert!(!tab_is_listed(&incognito_tab));
+    Ok(())
+}
+
 #[test]
 fn get_script_source() -> Result<(), failure::Error> {
     logging::enable_logging();
@@ -444,3 +986,144 @@ fn get_script_source() -> Result<(), failure::Error> {
 
     Ok(())
 }
+
+#[test]
+fn connect_to_port_reuses_open_tabs() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+    let launched = Browser::new(
+        LaunchOptionsBuilder::default()
+            .path(Some(default_executable().unwrap()))
+            .port(Some(port))
+            .build()
+            .unwrap(),
+    )?;
+    let opened_tab = launched.new_tab()?;
+
+    let connected = Browser::connect_to_port("localhost", port)?;
+    let tabs = connected.get_tabs().lock().unwrap();
+    assert!(tabs
+        .iter()
+        .any(|tab| tab.get_target_id() == opened_tab.get_target_id()));
+    Ok(())
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn detects_crash_and_relaunches() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let mut browser = Browser::new(
+        LaunchOptionsBuilder::default()
+            .path(Some(default_executable().unwrap()))
+            .build()
+            .unwrap(),
+    )?;
+    let events = browser.listen_to_events();
+    let tab = browser.wait_for_initial_tab()?;
+
+    std::process::Command::new("kill")
+        .args(&["-9", &browser.get_process_id().unwrap().to_string()])
+        .status()?;
+
+    match events.recv_timeout(Duration::from_secs(10))? {
+        BrowserEvent::Disconnected(crash) => assert!(crash.exit_status.is_some()),
+    }
+    let error = tab.get_target_info().unwrap_err();
+    assert!(error.downcast_ref::<BrowserCrashed>().is_some());
+
+    browser.relaunch()?;
+    browser.new_tab()?.navigate_to("about:blank")?;
+    Ok(())
+}
+
+#[test]
+fn crashed_tabs_fail_calls_and_stay_in_tab_list() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, tab) = dumb_server(include_str!("simple.html"));
+
+    // the navigation itself may or may not get a response before the renderer goes away
+    let _ = tab.navigate_to("chrome://crash");
+
+    Wait::with_timeout(Duration::from_secs(10)).until(|| {
+        if tab.is_crashed() {
+            Some(())
+        } else {
+            None
+        }
+    })?;
+    let error = tab.get_target_info().unwrap_err();
+    assert!(error.downcast_ref::<TabCrashed>().is_some());
+    // the crashed page is still open until it's closed
+    assert!(browser
+        .get_tabs()
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|other| other.get_target_id() == tab.get_target_id()));
+    Ok(())
+}
+
+#[test]
+fn closes_crashed_tabs_promptly() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (_, browser, _tab) = dumb_server(include_str!("simple.html"));
+    let tab = browser.new_tab()?;
+    let _ = tab.navigate_to("chrome://crash");
+    Wait::with_timeout(Duration::from_secs(10)).until(|| {
+        if tab.is_crashed() {
+            Some(())
+        } else {
+            None
+        }
+    })?;
+
+    let started = Instant::now();
+    tab.close(false)?;
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(tab.is_closed());
+    assert!(!browser
+        .get_tabs()
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|other| other.get_target_id() == tab.get_target_id()));
+    Ok(())
+}
+
+#[test]
+fn tab_pool_resets_and_replaces_tabs() -> Result<(), failure::Error> {
+    logging::enable_logging();
+    let (server, browser, _tab) = dumb_server(include_str!("simple.html"));
+    let pool = browser.new_tab_pool(2, true)?;
+
+    {
+        let first = pool.get()?;
+        let _second = pool.get()?;
+        assert!(pool.try_get().is_none());
+        first.navigate_to(&server.url())?.wait_until_navigated()?;
+    }
+
+    let tab = pool.get()?;
+    assert_eq!("about:blank", tab.get_url());
+
+    let _ = tab.navigate_to("chrome://crash");
+    Wait::with_timeout(Duration::from_secs(10)).until(|| {
+        if tab.is_crashed() {
+            Some(())
+        } else {
+            None
+        }
+    })?;
+
+    // the crashed tab is closed and replaced when it's given back
+    let started = Instant::now();
+    drop(tab);
+    let first = pool.get()?;
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(2, pool.size());
+    let second = pool.get()?;
+    assert!(!first.is_crashed() && !second.is_crashed());
+    Ok(())
+}